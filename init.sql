@@ -15,10 +15,13 @@ INSERT INTO Customer (Name) VALUES ('Peter');
 -- Create Order table
 CREATE TABLE "Order" (
     OrderId INTEGER PRIMARY KEY AUTOINCREMENT,
-    CustomerId INTEGER,
-    Amount REAL
+    CustomerId INTEGER REFERENCES Customer(Id),
+    Amount REAL,
+    OrderDate DATE
 );
 
-INSERT INTO "Order" (CustomerId, Amount) VALUES (1, 100.0);
-INSERT INTO "Order" (CustomerId, Amount) VALUES (2, 200.0);
-INSERT INTO "Order" (CustomerId, Amount) VALUES (1, 150.0);
+CREATE INDEX OrderCustomer ON "Order" (CustomerId, OrderId);
+
+INSERT INTO "Order" (CustomerId, Amount, OrderDate) VALUES (1, 100.0, '2025-01-15');
+INSERT INTO "Order" (CustomerId, Amount, OrderDate) VALUES (2, 200.0, '2025-02-28');
+INSERT INTO "Order" (CustomerId, Amount, OrderDate) VALUES (1, 150.0, '2025-03-01');
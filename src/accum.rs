@@ -0,0 +1,74 @@
+use std::cmp::Ordering;
+use crate::ast::Aggregate;
+use crate::value::Value;
+
+#[derive(Clone)]
+pub struct Accumulator {
+    aggregates: Vec<Aggregate>,
+    count: i64,
+    total: Value,
+    minimum: Value,
+    maximum: Value,
+}
+
+impl Accumulator {
+    pub fn new(aggregates: &[Aggregate]) -> Self {
+        Accumulator {
+            aggregates: aggregates.to_vec(),
+            count: 0,
+            total: Value::Integer(0),
+            minimum: Value::Unknown,
+            maximum: Value::Unknown,
+        }
+    }
+
+    pub fn add(&mut self, value: &Value) -> Result<(), String> {
+        if *value == Value::Unknown {
+            return Ok(());
+        }
+        self.count += 1;
+        if matches!(value, Value::Integer(_) | Value::Decimal(_)) {
+            self.total = self.total.add(value)?;
+        }
+        if self.minimum == Value::Unknown || value.compare(&self.minimum)? == Some(Ordering::Less) {
+            self.minimum = value.clone();
+        }
+        if self.maximum == Value::Unknown || value.compare(&self.maximum)? == Some(Ordering::Greater) {
+            self.maximum = value.clone();
+        }
+        Ok(())
+    }
+
+    pub fn result(&self, aggregate: Aggregate) -> Result<Value, String> {
+        if !self.aggregates.contains(&aggregate) {
+            return Err(format!("** {} was not requested in the ACCUMULATE statement.", keyword(aggregate)));
+        }
+        Ok(self.value(aggregate))
+    }
+
+    // What ACCUM reports for a group that has not accumulated any rows yet.
+    pub fn empty(aggregate: Aggregate) -> Value {
+        Accumulator::new(&[aggregate]).value(aggregate)
+    }
+
+    fn value(&self, aggregate: Aggregate) -> Value {
+        match aggregate {
+            Aggregate::Total => self.total.clone(),
+            Aggregate::Count => Value::Integer(self.count),
+            Aggregate::Average if self.count == 0 => Value::Integer(0),
+            Aggregate::Average => self.total.div(&Value::Integer(self.count)).unwrap_or(Value::Unknown),
+            Aggregate::Minimum => self.minimum.clone(),
+            Aggregate::Maximum => self.maximum.clone(),
+        }
+    }
+}
+
+pub fn keyword(aggregate: Aggregate) -> &'static str {
+    match aggregate {
+        Aggregate::Total => "TOTAL",
+        Aggregate::Count => "COUNT",
+        Aggregate::Average => "AVERAGE",
+        Aggregate::Maximum => "MAXIMUM",
+        Aggregate::Minimum => "MINIMUM",
+    }
+}
@@ -1,3 +1,6 @@
+use std::fmt;
+use crate::decimal::Decimal;
+
 #[derive(Debug, Clone)]
 pub enum Op {
     Eq,
@@ -8,30 +11,246 @@ pub enum Op {
     Ge,
     And,
     Or,
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Mod,
+    Begins,
+    Matches,
+    Contains,
+}
+
+#[derive(Debug, Clone)]
+pub enum UnaryOp {
+    Neg,
+    Not,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DataType {
+    Character,
+    Integer,
+    Int64,
+    Decimal,
+    Logical,
+    Date,
+    DateTime,
+    DateTimeTz,
 }
 
 #[derive(Debug, Clone)]
 pub enum Statement {
     ForEach {
-        table: String,
-        where_clause: Option<Expr>,
+        label: Option<String>,
+        phrases: Vec<RecordPhrase>,
+        sort: Vec<SortKey>,
+        breaks: bool,
         body: Vec<Statement>,
     },
-    FindFirst {
+    Find {
+        mode: FindMode,
         table: String,
+        key: Option<Expr>,
         where_clause: Option<Expr>,
+        no_error: bool,
+    },
+    Create { table: String, no_error: bool },
+    Delete { table: String, no_error: bool },
+    Assign {
+        assignments: Vec<(FieldRef, Expr)>,
+        no_error: bool,
+    },
+    DefineVariable {
+        name: String,
+        data_type: DataType,
+        initial: Option<Expr>,
+        // DECIMALS n: assigned values are rounded to n places.
+        decimals: Option<u32>,
+    },
+    DefineParameter {
+        mode: ParameterMode,
+        name: String,
+        data_type: DataType,
+    },
+    Procedure {
+        name: String,
+        body: Vec<Statement>,
+    },
+    Run {
+        name: String,
+        args: Vec<(ParameterMode, Expr)>,
     },
-    Create { table: String },
-    Delete { table: String },
-    Display { fields: Vec<String> },
+    // A FORWARD declaration has no body.
+    Function {
+        name: String,
+        returns: DataType,
+        params: Vec<(String, DataType)>,
+        body: Option<Vec<Statement>>,
+    },
+    Display { items: Vec<Expr> },
+    If {
+        condition: Expr,
+        then_branch: Box<Statement>,
+        else_branch: Option<Box<Statement>>,
+    },
+    Do {
+        label: Option<String>,
+        iteration: Option<Box<Iteration>>,
+        condition: Option<Expr>,
+        transaction: bool,
+        on_error: Option<UndoAction>,
+        body: Vec<Statement>,
+    },
+    Repeat {
+        label: Option<String>,
+        condition: Option<Expr>,
+        body: Vec<Statement>,
+    },
+    Accumulate { items: Vec<Accumulation> },
+    Undo {
+        label: Option<String>,
+        action: UndoAction,
+    },
+    Catch {
+        variable: String,
+        class: String,
+        body: Vec<Statement>,
+    },
+    Finally { body: Vec<Statement> },
+    Return {
+        error: bool,
+        value: Option<Expr>,
+    },
+    Leave { label: Option<String> },
+    Next { label: Option<String> },
+}
+
+#[derive(Debug, Clone)]
+pub enum UndoAction {
+    Leave(Option<String>),
+    Next(Option<String>),
+    Retry,
+    Throw(Option<String>),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ParameterMode {
+    Input,
+    Output,
+    InputOutput,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FindMode {
+    Unique,
+    First,
+    Last,
+    Next,
+    Prev,
+}
+
+#[derive(Debug, Clone)]
+pub struct RecordPhrase {
+    pub table: String,
+    pub of: Option<String>,
+    pub where_clause: Option<Expr>,
+    pub use_index: Option<String>,
+    pub outer_join: bool,
+}
+
+// A field or variable reference, optionally qualified as `table.field` or
+// `database.table.field`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FieldRef {
+    pub database: Option<String>,
+    pub table: Option<String>,
+    pub field: String,
+}
+
+impl FieldRef {
+    pub fn parse(name: &str) -> FieldRef {
+        let mut parts = name.rsplitn(3, '.');
+        let field = parts.next().unwrap_or_default().to_string();
+        let table = parts.next().map(str::to_string);
+        let database = parts.next().map(str::to_string);
+        FieldRef { database, table, field }
+    }
+
+    pub fn qualified(table: &str, field: &str) -> FieldRef {
+        FieldRef { database: None, table: Some(table.to_string()), field: field.to_string() }
+    }
+}
+
+impl fmt::Display for FieldRef {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for part in [&self.database, &self.table].into_iter().flatten() {
+            write!(f, "{}.", part)?;
+        }
+        write!(f, "{}", self.field)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct SortKey {
+    pub expr: Expr,
+    pub descending: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Aggregate {
+    Total,
+    Count,
+    Average,
+    Maximum,
+    Minimum,
+}
+
+#[derive(Debug, Clone)]
+pub struct Accumulation {
+    pub expr: Expr,
+    pub aggregates: Vec<Aggregate>,
+    pub groups: Vec<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Iteration {
+    pub variable: FieldRef,
+    pub from: Expr,
+    pub to: Expr,
+    pub by: Option<Expr>,
 }
 
 #[derive(Debug, Clone)]
 pub enum Expr {
-    Identifier(String),
+    Identifier(FieldRef),
     String(String),
     Number(i64),
-    Float(f64),
+    Decimal(Decimal),
+    // A date literal's three numbers in the order written.
+    Date([i64; 3]),
+    Logical(bool),
+    Unknown,
     Group(Box<Expr>),
     BinOp { left: Box<Expr>, op: Op, right: Box<Expr> },
+    Unary { op: UnaryOp, expr: Box<Expr> },
+    FirstOf(String),
+    LastOf(String),
+    Accum {
+        aggregate: Aggregate,
+        group: Option<String>,
+        expr: Box<Expr>,
+    },
+    CanFind {
+        mode: FindMode,
+        table: String,
+        where_clause: Option<Box<Expr>>,
+    },
+    Available(String),
+    Attribute {
+        handle: String,
+        name: String,
+        args: Vec<Expr>,
+    },
+    Call { name: String, args: Vec<Expr> },
 }
\ No newline at end of file
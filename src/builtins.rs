@@ -0,0 +1,442 @@
+use std::cmp::Ordering;
+use std::sync::atomic::{self, AtomicU64};
+use std::time::{SystemTime, UNIX_EPOCH};
+use rusqlite::functions::{Context, FunctionFlags};
+use rusqlite::types::Value as SqlValue;
+use rusqlite::{Connection, Result};
+use crate::ast::DataType;
+use crate::date;
+use crate::decimal::{self, Decimal};
+use crate::value::{self, Value};
+
+struct Builtin {
+    name: &'static str,
+    min_args: usize,
+    max_args: usize,
+    // None when the result takes the type of the arguments.
+    returns: Option<DataType>,
+}
+
+const fn builtin(name: &'static str, min_args: usize, max_args: usize, returns: Option<DataType>) -> Builtin {
+    Builtin { name, min_args, max_args, returns }
+}
+
+const CHARACTER: Option<DataType> = Some(DataType::Character);
+const INTEGER: Option<DataType> = Some(DataType::Integer);
+const DECIMAL: Option<DataType> = Some(DataType::Decimal);
+
+const BUILTINS: &[Builtin] = &[
+    builtin("SUBSTRING", 2, 3, CHARACTER),
+    builtin("INDEX", 2, 3, INTEGER),
+    builtin("R-INDEX", 2, 3, INTEGER),
+    builtin("REPLACE", 3, 3, CHARACTER),
+    builtin("TRIM", 1, 2, CHARACTER),
+    builtin("LEFT-TRIM", 1, 2, CHARACTER),
+    builtin("RIGHT-TRIM", 1, 2, CHARACTER),
+    builtin("CAPS", 1, 1, CHARACTER),
+    builtin("LC", 1, 1, CHARACTER),
+    builtin("LENGTH", 1, 1, INTEGER),
+    builtin("FILL", 2, 2, CHARACTER),
+    builtin("ENTRY", 2, 3, CHARACTER),
+    builtin("NUM-ENTRIES", 1, 2, INTEGER),
+    builtin("LOOKUP", 2, 3, INTEGER),
+    builtin("ROUND", 2, 2, DECIMAL),
+    builtin("TRUNCATE", 2, 2, DECIMAL),
+    builtin("ABS", 1, 1, None),
+    builtin("MAXIMUM", 2, usize::MAX, None),
+    builtin("MINIMUM", 2, usize::MAX, None),
+    builtin("RANDOM", 2, 2, INTEGER),
+    builtin("STRING", 1, 1, CHARACTER),
+    builtin("INTEGER", 1, 1, INTEGER),
+    builtin("DECIMAL", 1, 1, DECIMAL),
+    builtin("LOGICAL", 1, 1, Some(DataType::Logical)),
+    builtin("TODAY", 0, 0, Some(DataType::Date)),
+    builtin("NOW", 0, 0, Some(DataType::DateTimeTz)),
+    builtin("DATE", 1, 3, Some(DataType::Date)),
+    builtin("DAY", 1, 1, INTEGER),
+    builtin("MONTH", 1, 1, INTEGER),
+    builtin("YEAR", 1, 1, INTEGER),
+    builtin("WEEKDAY", 1, 1, INTEGER),
+];
+
+pub fn register(conn: &Connection) -> Result<()> {
+    let flags = FunctionFlags::SQLITE_UTF8 | FunctionFlags::SQLITE_DETERMINISTIC;
+    conn.create_scalar_function("abl_begins", 2, flags, |ctx| text_predicate(ctx, begins))?;
+    conn.create_scalar_function("abl_matches", 2, flags, |ctx| text_predicate(ctx, matches))?;
+    conn.create_scalar_function("abl_contains", 2, flags, |ctx| text_predicate(ctx, contains))?;
+    // ABL compares text case-insensitively and ignores trailing blanks.
+    conn.create_collation("ABL", |a, b| a.trim_end().to_lowercase().cmp(&b.trim_end().to_lowercase()))?;
+    // SQLite's `%` truncates toward zero; MODULO follows Value::modulo.
+    conn.create_scalar_function("abl_modulo", 2, flags, |ctx| {
+        let (a, b) = (argument(ctx, 0)?, argument(ctx, 1)?);
+        a.modulo(&b).map_err(|e| rusqlite::Error::UserFunctionError(e.into()))
+    })?;
+    for function in BUILTINS {
+        let flags = match function.name {
+            "RANDOM" | "TODAY" | "NOW" => FunctionFlags::SQLITE_UTF8,
+            _ => flags,
+        };
+        let name = function.name;
+        conn.create_scalar_function(&sql_name(name), -1, flags, move |ctx| {
+            let args = (0..ctx.len())
+                .map(|i| argument(ctx, i))
+                .collect::<Result<Vec<_>>>()?;
+            call(name, &args).map_err(|e| rusqlite::Error::UserFunctionError(e.into()))
+        })?;
+    }
+    Ok(())
+}
+
+pub fn is_function(name: &str) -> bool {
+    lookup(name).is_some()
+}
+
+// TODAY and NOW are written without parentheses.
+pub fn takes_args(name: &str) -> bool {
+    lookup(name).is_some_and(|f| f.max_args > 0)
+}
+
+pub fn returns(name: &str) -> Option<DataType> {
+    lookup(name)?.returns
+}
+
+// The name a built-in function is registered under with SQLite.
+pub fn sql_name(name: &str) -> String {
+    format!("abl_{}", name.to_lowercase().replace('-', "_"))
+}
+
+fn lookup(name: &str) -> Option<&'static Builtin> {
+    BUILTINS.iter().find(|f| f.name.eq_ignore_ascii_case(name))
+}
+
+// Evaluates a built-in function. As in ABL, an unknown argument makes the
+// result unknown.
+pub fn call(name: &str, args: &[Value]) -> Result<Value, String> {
+    let function = lookup(name).ok_or_else(|| format!("** Unknown function {}.", name))?;
+    if args.len() < function.min_args || args.len() > function.max_args {
+        return Err(format!("** Mismatched number of parameters passed to routine {}. (3234)", function.name));
+    }
+    if args.contains(&Value::Unknown) {
+        return Ok(Value::Unknown);
+    }
+    let text = |i: usize| args.get(i).map(|v| v.to_string());
+    let int = |i: usize| -> Result<Option<i64>, String> {
+        match args.get(i).map(|v| v.clone().coerce(DataType::Integer)).transpose()? {
+            Some(Value::Integer(n)) => Ok(Some(n)),
+            _ => Ok(None),
+        }
+    };
+    let delimiter = || text(2).unwrap_or_else(|| ",".to_string());
+    let source = text(0).unwrap_or_default();
+
+    Ok(match function.name {
+        "SUBSTRING" => {
+            let start = int(1)?.unwrap_or(1);
+            if start < 1 {
+                return Err("** Starting position for SUBSTRING, OVERLAY, etc. must be 1 or greater. (82)".to_string());
+            }
+            let chars = source.chars().skip(start as usize - 1);
+            Value::Character(match int(2)? {
+                Some(length) if length >= 0 => chars.take(length as usize).collect(),
+                _ => chars.collect(),
+            })
+        }
+        "INDEX" => {
+            let start = int(2)?.unwrap_or(1).max(1) as usize - 1;
+            Value::Integer(positions(&source, &text(1).unwrap_or_default()).find(|&p| p >= start).map_or(0, |p| p as i64 + 1))
+        }
+        "R-INDEX" => {
+            let start = int(2)?.map(|s| s.max(0) as usize).unwrap_or(usize::MAX);
+            let last = positions(&source, &text(1).unwrap_or_default()).filter(|&p| p < start).last();
+            Value::Integer(last.map_or(0, |p| p as i64 + 1))
+        }
+        "REPLACE" => Value::Character(replace(&source, &text(1).unwrap_or_default(), &text(2).unwrap_or_default())),
+        "TRIM" | "LEFT-TRIM" | "RIGHT-TRIM" => {
+            let chars = text(1);
+            let trimmed = |c: char| match &chars {
+                Some(set) => set.contains(c),
+                None => c.is_whitespace(),
+            };
+            Value::Character(match function.name {
+                "LEFT-TRIM" => source.trim_start_matches(trimmed),
+                "RIGHT-TRIM" => source.trim_end_matches(trimmed),
+                _ => source.trim_matches(trimmed),
+            }
+            .to_string())
+        }
+        "CAPS" => Value::Character(source.to_uppercase()),
+        "LC" => Value::Character(source.to_lowercase()),
+        "LENGTH" => Value::Integer(source.chars().count() as i64),
+        "FILL" => Value::Character(source.repeat(int(1)?.unwrap_or(0).max(0) as usize)),
+        "ENTRY" => {
+            let n = int(0)?.unwrap_or(0);
+            let list = text(1).unwrap_or_default();
+            let entry = usize::try_from(n - 1).ok().and_then(|i| list.split(delimiter().as_str()).nth(i));
+            match entry {
+                Some(entry) => Value::Character(entry.to_string()),
+                None => return Err(format!("** Entry {} is outside the range of list {}. (560)", n, list)),
+            }
+        }
+        "NUM-ENTRIES" => {
+            let delimiter = text(1).unwrap_or_else(|| ",".to_string());
+            Value::Integer(match source.as_str() {
+                "" => 0,
+                list => list.split(delimiter.as_str()).count() as i64,
+            })
+        }
+        "LOOKUP" => {
+            let list = text(1).unwrap_or_default();
+            let found = match list.as_str() {
+                "" => None,
+                list => list.split(delimiter().as_str()).position(|e| e.eq_ignore_ascii_case(&source)),
+            };
+            Value::Integer(found.map_or(0, |i| i as i64 + 1))
+        }
+        "ROUND" | "TRUNCATE" => {
+            let value = match args[0].clone().coerce(DataType::Decimal)? {
+                Value::Decimal(d) => d,
+                _ => Decimal::ZERO,
+            };
+            let places = u32::try_from(int(1)?.unwrap_or(0)).unwrap_or(0);
+            Value::Decimal(if function.name == "ROUND" { value.round(places) } else { value.truncate(places) })
+        }
+        "ABS" => match &args[0] {
+            Value::Integer(n) => Value::Integer(n.checked_abs().ok_or_else(value::too_large)?),
+            Value::Decimal(d) => Value::Decimal(d.checked_abs().ok_or_else(decimal::too_large)?),
+            _ => return Err("** Incompatible data types in expression or assignment. (223)".to_string()),
+        },
+        "MAXIMUM" | "MINIMUM" => {
+            let wanted = if function.name == "MAXIMUM" { Ordering::Greater } else { Ordering::Less };
+            let mut best = args[0].clone();
+            for arg in &args[1..] {
+                if arg.compare(&best)? == Some(wanted) {
+                    best = arg.clone();
+                }
+            }
+            best
+        }
+        "RANDOM" => {
+            let (low, high) = (int(0)?.unwrap_or(0), int(1)?.unwrap_or(0));
+            if high < low {
+                return Err(format!("** RANDOM high value {} is lower than the low value {}.", high, low));
+            }
+            // Up to 2^64 values, which needs more than 64 bits.
+            let span = (i128::from(high) - i128::from(low) + 1) as u128;
+            Value::Integer((i128::from(low) + (u128::from(random()) % span) as i128) as i64)
+        }
+        "STRING" => Value::Character(source),
+        "INTEGER" => args[0].clone().coerce(DataType::Integer)?,
+        "DECIMAL" => args[0].clone().coerce(DataType::Decimal)?,
+        "LOGICAL" => args[0].clone().coerce(DataType::Logical)?,
+        "TODAY" => Value::Date(date::today()),
+        "NOW" => Value::DateTimeTz(date::now(), date::offset()),
+        "DATE" if args.len() == 3 => Value::Date(date::date(int(2)?.unwrap_or(0), int(0)?.unwrap_or(0), int(1)?.unwrap_or(0))?),
+        "DATE" if args.len() == 1 => args[0].clone().coerce(DataType::Date)?,
+        "DATE" => return Err("** DATE takes a month, day and year or a single value. (3234)".to_string()),
+        "DAY" | "MONTH" | "YEAR" | "WEEKDAY" => {
+            let days = match args[0].clone().coerce(DataType::Date)? {
+                Value::Date(days) => days,
+                _ => return Ok(Value::Unknown),
+            };
+            let (year, month, day) = date::to_civil(days);
+            Value::Integer(match function.name {
+                "DAY" => day,
+                "MONTH" => month,
+                "YEAR" => year,
+                _ => date::weekday(days),
+            })
+        }
+        _ => unreachable!("{} is in the built-in table", function.name),
+    })
+}
+
+// Character positions where `target` occurs in `source`, ignoring case.
+fn positions(source: &str, target: &str) -> impl Iterator<Item = usize> {
+    let source: Vec<char> = source.to_lowercase().chars().collect();
+    let target: Vec<char> = target.to_lowercase().chars().collect();
+    let count = if target.is_empty() { 0 } else { (source.len() + 1).saturating_sub(target.len()) };
+    (0..count).filter(move |&i| source[i..i + target.len()] == target[..])
+}
+
+fn replace(source: &str, from: &str, to: &str) -> String {
+    let chars: Vec<char> = source.chars().collect();
+    let width = from.chars().count();
+    let mut result = String::new();
+    let mut next = 0;
+    for start in positions(source, from) {
+        if start >= next {
+            result.extend(&chars[next..start]);
+            result.push_str(to);
+            next = start + width;
+        }
+    }
+    result.extend(&chars[next..]);
+    result
+}
+
+// A xorshift generator seeded from the clock; RANDOM needs no better.
+fn random() -> u64 {
+    static STATE: AtomicU64 = AtomicU64::new(0);
+    let mut x = STATE.load(atomic::Ordering::Relaxed);
+    if x == 0 {
+        x = SystemTime::now().duration_since(UNIX_EPOCH).map_or(1, |d| d.as_nanos() as u64) | 1;
+    }
+    x ^= x << 13;
+    x ^= x >> 7;
+    x ^= x << 17;
+    STATE.store(x, atomic::Ordering::Relaxed);
+    x
+}
+
+pub fn argument(ctx: &Context, i: usize) -> Result<Value> {
+    Value::try_from(ctx.get::<SqlValue>(i)?).map_err(|e| rusqlite::Error::UserFunctionError(e.into()))
+}
+
+fn text_predicate(ctx: &Context, f: fn(&str, &str) -> bool) -> Result<Option<bool>> {
+    let a: Option<String> = ctx.get(0)?;
+    let b: Option<String> = ctx.get(1)?;
+    Ok(match (a, b) {
+        (Some(a), Some(b)) => Some(f(&a, &b)),
+        _ => None,
+    })
+}
+
+pub fn begins(text: &str, prefix: &str) -> bool {
+    text.to_lowercase().starts_with(prefix.trim_end().to_lowercase().as_str())
+}
+
+// `*` matches any run of characters, `.` any single character and `~`
+// escapes the next one; the comparison is case-insensitive.
+pub fn matches(text: &str, pattern: &str) -> bool {
+    let text: Vec<char> = text.trim_end().to_lowercase().chars().collect();
+    let pattern = pattern.to_lowercase();
+    let mut chars = pattern.chars();
+    let mut elements = Vec::new();
+    while let Some(c) = chars.next() {
+        elements.push(match c {
+            '*' => Wildcard::Any,
+            '.' => Wildcard::One,
+            '~' => Wildcard::Char(chars.next().unwrap_or('~')),
+            c => Wildcard::Char(c),
+        });
+    }
+    matches_elements(&text, &elements)
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Wildcard {
+    Any,
+    One,
+    Char(char),
+}
+
+// Greedy two-pointer match: on a mismatch, retry from the most recent `*`
+// with it absorbing one more character, so the cost is O(text × pattern).
+fn matches_elements(text: &[char], pattern: &[Wildcard]) -> bool {
+    let (mut t, mut p) = (0, 0);
+    let mut star: Option<(usize, usize)> = None;
+    while t < text.len() {
+        match pattern.get(p) {
+            Some(Wildcard::Any) => {
+                star = Some((p, t));
+                p += 1;
+            }
+            Some(Wildcard::One) => {
+                t += 1;
+                p += 1;
+            }
+            Some(Wildcard::Char(c)) if *c == text[t] => {
+                t += 1;
+                p += 1;
+            }
+            _ => match star {
+                Some((star_p, star_t)) => {
+                    star = Some((star_p, star_t + 1));
+                    p = star_p + 1;
+                    t = star_t + 1;
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|e| *e == Wildcard::Any)
+}
+
+// Word search: `|` or `^` separate alternatives, `&` or blanks require every
+// term, `!` negates a term and a trailing `*` turns it into a prefix match.
+pub fn contains(text: &str, search: &str) -> bool {
+    let words: Vec<String> = text
+        .split(|c: char| !c.is_alphanumeric() && c != '-' && c != '_')
+        .filter(|w| !w.is_empty())
+        .map(|w| w.to_lowercase())
+        .collect();
+    search.split(['|', '^']).any(|alternative| {
+        let terms: Vec<&str> = alternative
+            .split(|c: char| c == '&' || c.is_whitespace())
+            .filter(|t| !t.is_empty())
+            .collect();
+        !terms.is_empty()
+            && terms.iter().all(|term| {
+                let (negated, term) = match term.strip_prefix('!') {
+                    Some(t) => (true, t),
+                    None => (false, *term),
+                };
+                let term = term.to_lowercase();
+                let found = match term.strip_suffix('*') {
+                    Some(prefix) => words.iter().any(|w| w.starts_with(prefix)),
+                    None => words.contains(&term),
+                };
+                found != negated
+            })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matches_wildcards() {
+        assert!(matches("John", "j*"));
+        assert!(matches("John  ", "*OHN"));
+        assert!(matches("John", "j.hn"));
+        assert!(!matches("John", "j.n"));
+        assert!(matches("a*b", "a~*b"));
+        assert!(!matches("axb", "a~*b"));
+        assert!(matches("100~", "100~"));
+        assert!(matches("", "*"));
+        assert!(!matches("", "."));
+        assert!(matches("abcbcd", "a*bcd"));
+        assert!(!matches("abcbce", "a*bcd"));
+    }
+
+    #[test]
+    fn matches_pathological_patterns() {
+        let text = "a".repeat(40);
+        assert!(!matches(&text, "*a*a*a*a*a*a*a*a*a*a*a*a*b"));
+        assert!(matches(&(text.clone() + "b"), "*a*a*a*a*a*a*a*a*a*a*a*a*b"));
+        let text = "a".repeat(10_000);
+        let pattern = format!("{}b", "*a.".repeat(500));
+        assert!(!matches(&text, &pattern));
+    }
+
+    #[test]
+    fn integer_overflow_is_an_error() {
+        let min = Value::Integer(i64::MIN);
+        assert!(call("ABS", std::slice::from_ref(&min)).is_err());
+        assert_eq!(call("ABS", &[Value::Integer(-5)]), Ok(Value::Integer(5)));
+        assert!(min.negate().is_err());
+    }
+
+    #[test]
+    fn random_spans_the_whole_integer_range() {
+        for (low, high) in [(i64::MIN, i64::MAX), (-1, i64::MAX), (0, i64::MAX), (i64::MIN, -9)] {
+            let Ok(Value::Integer(n)) = call("RANDOM", &[Value::Integer(low), Value::Integer(high)]) else {
+                panic!("RANDOM({}, {}) failed", low, high);
+            };
+            assert!((low..=high).contains(&n));
+        }
+        assert_eq!(call("RANDOM", &[Value::Integer(7), Value::Integer(7)]), Ok(Value::Integer(7)));
+        assert!(call("RANDOM", &[Value::Integer(8), Value::Integer(7)]).is_err());
+    }
+}
@@ -0,0 +1,320 @@
+use std::sync::OnceLock;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+// Dates count days and datetimes count milliseconds from 1970-01-01. In
+// SQLite they are stored as ISO 8601 text, which sorts chronologically, and
+// DATETIME-TZ values are stored in UTC so that they sort the same way.
+
+pub const MS_PER_DAY: i64 = 86_400_000;
+
+static FORMAT: OnceLock<String> = OnceLock::new();
+static OFFSET: OnceLock<i32> = OnceLock::new();
+
+// The order of month, day and year in date literals and displayed dates,
+// as set by the -d startup parameter. Defaults to mdy.
+pub fn set_format(format: &str) -> Result<(), String> {
+    let format = format.to_lowercase();
+    let mut parts: Vec<char> = format.chars().collect();
+    parts.sort_unstable();
+    if parts != ['d', 'm', 'y'] {
+        return Err(format!("Invalid date format {}: expected an order of m, d and y such as mdy.", format));
+    }
+    let _ = FORMAT.set(format);
+    Ok(())
+}
+
+fn format() -> &'static str {
+    FORMAT.get().map_or("mdy", String::as_str)
+}
+
+// Builds a date from three numbers given in the session's order, as written
+// in a literal such as 12/31/2025. Two-digit years fall in 1950-2049.
+pub fn from_parts(parts: [i64; 3]) -> Result<i64, String> {
+    let (mut year, mut month, mut day) = (0, 0, 0);
+    for (part, value) in format().chars().zip(parts) {
+        match part {
+            'y' => year = value,
+            'm' => month = value,
+            _ => day = value,
+        }
+    }
+    if (0..100).contains(&year) {
+        year += if year < 50 { 2000 } else { 1900 };
+    }
+    from_civil(year, month, day).ok_or_else(invalid)
+}
+
+pub fn invalid() -> String {
+    "** Invalid date input. (85)".to_string()
+}
+
+// Dates run from 0001-01-01 to 9999-12-31, the years ISO text holds in
+// four digits and SQLite's date functions understand.
+const YEARS: std::ops::RangeInclusive<i64> = 1..=9999;
+const FIRST_DAY: i64 = -719_162;
+const LAST_DAY: i64 = 2_932_896;
+
+pub fn out_of_range() -> String {
+    "** Date is out of range.".to_string()
+}
+
+// A day or millisecond count from date arithmetic, None if it overflowed.
+pub fn checked_days(days: Option<i64>) -> Result<i64, String> {
+    days.filter(|&days| in_range(days)).ok_or_else(out_of_range)
+}
+
+pub fn checked_ms(ms: Option<i64>) -> Result<i64, String> {
+    ms.filter(|ms| in_range(ms.div_euclid(MS_PER_DAY))).ok_or_else(out_of_range)
+}
+
+fn in_range(days: i64) -> bool {
+    (FIRST_DAY..=LAST_DAY).contains(&days)
+}
+
+fn from_civil(year: i64, month: i64, day: i64) -> Option<i64> {
+    if !YEARS.contains(&year) || !(1..=12).contains(&month) || day < 1 || day > days_in_month(year, month) {
+        return None;
+    }
+    // Howard Hinnant's days_from_civil.
+    let y = if month <= 2 { year - 1 } else { year };
+    let era = y.div_euclid(400);
+    let yoe = y - era * 400;
+    let mp = (month + 9) % 12;
+    let doy = (153 * mp + 2) / 5 + day - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    Some(era * 146_097 + doe - 719_468)
+}
+
+pub fn to_civil(days: i64) -> (i64, i64, i64) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z - era * 146_097;
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+fn days_in_month(year: i64, month: i64) -> i64 {
+    match month {
+        2 if year % 4 == 0 && (year % 100 != 0 || year % 400 == 0) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+// 1 for Sunday through 7 for Saturday; 1970-01-01 was a Thursday.
+pub fn weekday(days: i64) -> i64 {
+    (days + 4).rem_euclid(7) + 1
+}
+
+pub fn date(year: i64, month: i64, day: i64) -> Result<i64, String> {
+    from_civil(year, month, day).ok_or_else(invalid)
+}
+
+// The local time zone's offset from UTC in minutes, read once at startup.
+pub fn set_offset(minutes: i32) {
+    let _ = OFFSET.set(minutes);
+}
+
+pub fn offset() -> i32 {
+    OFFSET.get().copied().unwrap_or(0)
+}
+
+// TODAY is the local date; NOW is the current instant, paired with offset().
+pub fn today() -> i64 {
+    (now() + i64::from(offset()) * 60_000).div_euclid(MS_PER_DAY)
+}
+
+pub fn now() -> i64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |d| d.as_millis() as i64)
+}
+
+pub fn display_date(days: i64) -> String {
+    let (year, month, day) = to_civil(days);
+    let parts: Vec<String> = format()
+        .chars()
+        .map(|part| match part {
+            'y' => format!("{:04}", year),
+            'm' => format!("{:02}", month),
+            _ => format!("{:02}", day),
+        })
+        .collect();
+    parts.join("/")
+}
+
+pub fn display_datetime(ms: i64) -> String {
+    format!("{} {}", display_date(ms.div_euclid(MS_PER_DAY)), time_of_day(ms))
+}
+
+pub fn display_datetime_tz(ms: i64, offset: i32) -> String {
+    format!("{}{}", display_datetime(ms + i64::from(offset) * 60_000), display_offset(offset))
+}
+
+pub fn iso_date(days: i64) -> String {
+    let (year, month, day) = to_civil(days);
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+pub fn iso_datetime(ms: i64) -> String {
+    format!("{}T{}", iso_date(ms.div_euclid(MS_PER_DAY)), time_of_day(ms))
+}
+
+pub fn iso_datetime_tz(ms: i64) -> String {
+    format!("{}+00:00", iso_datetime(ms))
+}
+
+fn time_of_day(ms: i64) -> String {
+    let ms = ms.rem_euclid(MS_PER_DAY);
+    format!("{:02}:{:02}:{:02}.{:03}", ms / 3_600_000, ms / 60_000 % 60, ms / 1000 % 60, ms % 1000)
+}
+
+fn display_offset(offset: i32) -> String {
+    let sign = if offset < 0 { '-' } else { '+' };
+    format!("{}{:02}:{:02}", sign, offset.abs() / 60, offset.abs() % 60)
+}
+
+// Accepts ISO text as stored in SQLite as well as dates written in the
+// session's order with `/`, `-` or `.` separators.
+pub fn parse_date(text: &str) -> Option<i64> {
+    let text = text.trim();
+    if let Some(days) = parse_iso_date(text) {
+        return Some(days);
+    }
+    let parts: Vec<i64> = text.split(['/', '-', '.']).map(|p| p.parse().ok()).collect::<Option<_>>()?;
+    from_parts(parts.try_into().ok()?).ok()
+}
+
+fn parse_iso_date(text: &str) -> Option<i64> {
+    let mut parts = text.splitn(3, '-');
+    let year = parts.next().filter(|p| p.len() == 4)?.parse().ok()?;
+    let month = parts.next()?.parse().ok()?;
+    let day = parts.next()?.parse().ok()?;
+    from_civil(year, month, day)
+}
+
+// A date, optionally followed by `T` or a blank and HH:MM[:SS[.mmm]].
+pub fn parse_datetime(text: &str) -> Option<i64> {
+    let text = text.trim();
+    let (date, time) = match text.split_once(['T', ' ']) {
+        Some((date, time)) => (date, Some(time.trim())),
+        None => (text, None),
+    };
+    let days = parse_date(date)?;
+    let ms = match time {
+        Some(time) => parse_time(time)?,
+        None => 0,
+    };
+    Some(days * MS_PER_DAY + ms)
+}
+
+// A datetime with an optional trailing `Z` or +HH:MM / -HH:MM offset. The
+// result is the UTC instant and the offset in minutes.
+pub fn parse_datetime_tz(text: &str) -> Option<(i64, i32)> {
+    let text = text.trim();
+    if let Some(local) = text.strip_suffix('Z') {
+        return Some((parse_datetime(local)?, 0));
+    }
+    let split = text.rfind(['+', '-']).filter(|&i| text[..i].contains(':'));
+    let (local, offset) = match split {
+        Some(i) => {
+            let (hours, minutes) = text[i + 1..].split_once(':')?;
+            let minutes = hours.parse::<i32>().ok()? * 60 + minutes.parse::<i32>().ok()?;
+            (&text[..i], if text[i..].starts_with('-') { -minutes } else { minutes })
+        }
+        None => (text, 0),
+    };
+    Some((parse_datetime(local)? - i64::from(offset) * 60_000, offset))
+}
+
+fn parse_time(text: &str) -> Option<i64> {
+    let (clock, millis) = match text.split_once('.') {
+        Some((clock, millis)) => (clock, format!("{:0<3}", millis).get(..3)?.parse::<i64>().ok()?),
+        None => (text, 0),
+    };
+    let parts: Vec<i64> = clock.split(':').map(|p| p.parse().ok()).collect::<Option<_>>()?;
+    let (hours, minutes, seconds) = match parts.as_slice() {
+        [h, m] => (*h, *m, 0),
+        [h, m, s] => (*h, *m, *s),
+        _ => return None,
+    };
+    if hours > 23 || minutes > 59 || seconds > 59 {
+        return None;
+    }
+    Some(((hours * 60 + minutes) * 60 + seconds) * 1000 + millis)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn civil_round_trip() {
+        assert_eq!(from_civil(1970, 1, 1), Some(0));
+        assert_eq!(from_civil(1969, 12, 31), Some(-1));
+        assert_eq!(from_civil(2000, 3, 1), Some(11_017));
+        for days in [FIRST_DAY, -719_000, -1, 0, 59, 60, 11_016, LAST_DAY] {
+            let (year, month, day) = to_civil(days);
+            assert_eq!(from_civil(year, month, day), Some(days));
+        }
+        assert_eq!(to_civil(-719_468), (0, 3, 1));
+        assert_eq!(to_civil(-719_469), (0, 2, 29));
+    }
+
+    #[test]
+    fn leap_years_and_month_ends() {
+        assert!(from_civil(2024, 2, 29).is_some());
+        assert!(from_civil(2000, 2, 29).is_some());
+        assert_eq!(from_civil(1900, 2, 29), None);
+        assert_eq!(from_civil(2025, 2, 29), None);
+        assert_eq!(from_civil(2025, 4, 31), None);
+        assert!(from_civil(2025, 12, 31).is_some());
+        assert_eq!(from_civil(2025, 13, 1), None);
+        assert_eq!(from_civil(2025, 1, 0), None);
+        assert_eq!(from_civil(2024, 3, 1).zip(from_civil(2024, 2, 28)).map(|(a, b)| a - b), Some(2));
+    }
+
+    #[test]
+    fn range_limits() {
+        assert_eq!(from_civil(1, 1, 1), Some(FIRST_DAY));
+        assert_eq!(from_civil(9999, 12, 31), Some(LAST_DAY));
+        assert_eq!(from_civil(0, 12, 31), None);
+        assert_eq!(from_civil(10000, 1, 1), None);
+        assert_eq!(checked_days(Some(LAST_DAY)), Ok(LAST_DAY));
+        assert!(checked_days(Some(LAST_DAY + 1)).is_err());
+        assert!(checked_days(Some(FIRST_DAY - 1)).is_err());
+        assert!(checked_days(None).is_err());
+        assert!(checked_ms(Some(i64::MIN)).is_err());
+    }
+
+    #[test]
+    fn iso_text_round_trips() {
+        for days in [FIRST_DAY, -1, 0, LAST_DAY] {
+            assert_eq!(parse_iso_date(&iso_date(days)), Some(days));
+            let ms = days * MS_PER_DAY + MS_PER_DAY - 1;
+            assert_eq!(parse_datetime(&iso_datetime(ms)), Some(ms));
+            assert_eq!(parse_datetime_tz(&iso_datetime_tz(ms)), Some((ms, 0)));
+        }
+        assert_eq!(iso_date(FIRST_DAY), "0001-01-01");
+        assert_eq!(iso_date(LAST_DAY), "9999-12-31");
+    }
+
+    #[test]
+    fn two_digit_years() {
+        assert_eq!(from_parts([1, 1, 95]), date(1995, 1, 1));
+        assert_eq!(from_parts([1, 1, 5]), date(2005, 1, 1));
+        assert!(from_parts([1, 1, -5]).is_err());
+        assert_eq!(from_parts([1, 1, 100]), date(100, 1, 1));
+    }
+
+    #[test]
+    fn weekdays() {
+        assert_eq!(weekday(0), 5);
+        assert_eq!(weekday(-1), 4);
+        assert_eq!(weekday(from_civil(2025, 12, 31).unwrap()), 4);
+    }
+}
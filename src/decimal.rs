@@ -0,0 +1,238 @@
+use std::fmt;
+
+// A fixed-point number with ABL's default of ten decimal places, held as a
+// count of 10^-10 units. Results that need more places round half away from
+// zero, the way ROUND and DECIMALS do.
+
+pub const SCALE: u32 = 10;
+
+const ONE: i128 = 10_000_000_000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Decimal(i128);
+
+pub fn too_large() -> String {
+    "** Decimal number is too large. (536)".to_string()
+}
+
+impl Decimal {
+    pub const ZERO: Decimal = Decimal(0);
+
+    pub fn from_int(i: i64) -> Decimal {
+        Decimal(i128::from(i) * ONE)
+    }
+
+    // Plain decimal notation with an optional sign, such as -12.50. Places
+    // beyond the tenth are rounded.
+    pub fn parse(text: &str) -> Option<Decimal> {
+        let text = text.trim();
+        let (negative, digits) = match text.strip_prefix('-') {
+            Some(rest) => (true, rest),
+            None => (false, text.strip_prefix('+').unwrap_or(text)),
+        };
+        let (whole, fraction) = digits.split_once('.').unwrap_or((digits, ""));
+        if whole.is_empty() && fraction.is_empty()
+            || !whole.chars().chain(fraction.chars()).all(|c| c.is_ascii_digit())
+        {
+            return None;
+        }
+        let padded = format!("{:0<width$}", fraction, width = SCALE as usize);
+        let (kept, dropped) = padded.split_at(SCALE as usize);
+        let mut units: i128 = 0;
+        for c in whole.chars().chain(kept.chars()) {
+            units = units.checked_mul(10)?.checked_add(i128::from(c as u8 - b'0'))?;
+        }
+        if dropped.starts_with(|c: char| c >= '5') {
+            units = units.checked_add(1)?;
+        }
+        Some(Decimal(if negative { -units } else { units }))
+    }
+
+    // SQLite only stores binary floating point; the shortest text that reads
+    // back as the same double is the decimal the user wrote.
+    pub fn from_f64(f: f64) -> Option<Decimal> {
+        if f.is_finite() {
+            Decimal::parse(&f.to_string())
+        } else {
+            None
+        }
+    }
+
+    pub fn to_f64(self) -> f64 {
+        self.to_string().parse().unwrap_or(f64::NAN)
+    }
+
+    pub fn is_zero(self) -> bool {
+        self.0 == 0
+    }
+
+    pub fn checked_abs(self) -> Option<Decimal> {
+        self.0.checked_abs().map(Decimal)
+    }
+
+    pub fn checked_neg(self) -> Option<Decimal> {
+        self.0.checked_neg().map(Decimal)
+    }
+
+    pub fn checked_add(self, other: Decimal) -> Option<Decimal> {
+        self.0.checked_add(other.0).map(Decimal)
+    }
+
+    pub fn checked_sub(self, other: Decimal) -> Option<Decimal> {
+        self.0.checked_sub(other.0).map(Decimal)
+    }
+
+    // Splits both operands into whole and fractional units so the product
+    // only overflows when the result itself does.
+    pub fn checked_mul(self, other: Decimal) -> Option<Decimal> {
+        let (a, b) = (self.0.unsigned_abs(), other.0.unsigned_abs());
+        let (a_whole, a_fraction) = (a / ONE as u128, a % ONE as u128);
+        let (b_whole, b_fraction) = (b / ONE as u128, b % ONE as u128);
+        let fractions = a_fraction * b_fraction;
+        let rounded = fractions / ONE as u128 + u128::from(fractions % ONE as u128 >= ONE as u128 / 2);
+        let units = a_whole
+            .checked_mul(b)?
+            .checked_add(a_fraction.checked_mul(b_whole)?)?
+            .checked_add(rounded)?;
+        signed(units, (self.0 < 0) != (other.0 < 0))
+    }
+
+    // Long division, one decimal place at a time. None for a zero divisor.
+    pub fn checked_div(self, other: Decimal) -> Option<Decimal> {
+        let (a, b) = (self.0.unsigned_abs(), other.0.unsigned_abs());
+        if b == 0 {
+            return None;
+        }
+        let mut units = a / b;
+        let mut remainder = a % b;
+        for _ in 0..SCALE {
+            remainder = remainder.checked_mul(10)?;
+            units = units.checked_mul(10)?.checked_add(remainder / b)?;
+            remainder %= b;
+        }
+        if remainder.checked_mul(2)? >= b {
+            units = units.checked_add(1)?;
+        }
+        signed(units, (self.0 < 0) != (other.0 < 0))
+    }
+
+    // Rounds half away from zero to `places` decimal places.
+    pub fn round(self, places: u32) -> Decimal {
+        if places >= SCALE {
+            return self;
+        }
+        let step = 10i128.pow(SCALE - places);
+        let remainder = self.0 % step;
+        let mut units = self.0 - remainder;
+        if remainder.abs() * 2 >= step {
+            units += step * self.0.signum();
+        }
+        Decimal(units)
+    }
+
+    pub fn truncate(self, places: u32) -> Decimal {
+        if places >= SCALE {
+            return self;
+        }
+        let step = 10i128.pow(SCALE - places);
+        Decimal(self.0 - self.0 % step)
+    }
+
+    // Rounded and padded to exactly `places` decimal places, the way a
+    // DECIMALS n variable displays.
+    pub fn to_fixed(self, places: u32) -> String {
+        let places = places.min(SCALE);
+        let rounded = self.round(places);
+        let sign = if rounded.0 < 0 { "-" } else { "" };
+        let units = rounded.0.unsigned_abs();
+        let whole = units / ONE as u128;
+        if places == 0 {
+            return format!("{}{}", sign, whole);
+        }
+        let fraction = format!("{:010}", units % ONE as u128);
+        format!("{}{}.{}", sign, whole, &fraction[..places as usize])
+    }
+
+    // The nearest integer, halves away from zero.
+    pub fn to_int(self) -> Option<i64> {
+        i64::try_from(self.round(0).0 / ONE).ok()
+    }
+}
+
+fn signed(units: u128, negative: bool) -> Option<Decimal> {
+    let units = i128::try_from(units).ok()?;
+    Some(Decimal(if negative { -units } else { units }))
+}
+
+// Trailing zeros are dropped, so 100.0 displays as 100 and 2.50 as 2.5.
+impl fmt::Display for Decimal {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let sign = if self.0 < 0 { "-" } else { "" };
+        let units = self.0.unsigned_abs();
+        let whole = units / ONE as u128;
+        let fraction = format!("{:010}", units % ONE as u128);
+        let fraction = fraction.trim_end_matches('0');
+        if fraction.is_empty() {
+            write!(f, "{}{}", sign, whole)
+        } else {
+            write!(f, "{}{}.{}", sign, whole, fraction)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dec(text: &str) -> Decimal {
+        Decimal::parse(text).unwrap()
+    }
+
+    #[test]
+    fn parse_edges() {
+        assert_eq!(dec("12.50").to_string(), "12.5");
+        assert_eq!(dec("-.5").to_string(), "-0.5");
+        assert_eq!(dec("+7.").to_string(), "7");
+        assert_eq!(dec("0.00000000005"), dec("0.0000000001"));
+        assert_eq!(dec("0.00000000004"), Decimal::ZERO);
+        assert_eq!(Decimal::parse(""), None);
+        assert_eq!(Decimal::parse("."), None);
+        assert_eq!(Decimal::parse("1.2.3"), None);
+        assert_eq!(Decimal::parse("1e5"), None);
+        assert_eq!(Decimal::parse(&"9".repeat(40)), None);
+    }
+
+    #[test]
+    fn round_half_away_from_zero() {
+        assert_eq!(dec("2.5").round(0), dec("3"));
+        assert_eq!(dec("-2.5").round(0), dec("-3"));
+        assert_eq!(dec("2.345").round(2), dec("2.35"));
+        assert_eq!(dec("-2.344").round(2), dec("-2.34"));
+        assert_eq!(dec("1.23").round(SCALE + 2), dec("1.23"));
+        assert_eq!(dec("-2.789").truncate(1), dec("-2.7"));
+    }
+
+    #[test]
+    fn checked_mul_and_div_edges() {
+        assert_eq!(dec("0.1").checked_mul(dec("0.1")), Some(dec("0.01")));
+        assert_eq!(dec("-1.5").checked_mul(dec("2")), Some(dec("-3")));
+        assert_eq!(dec("0.00001").checked_mul(dec("0.00001")), Some(dec("0.0000000001")));
+        assert_eq!(dec("0.00001").checked_mul(dec("0.000004")), Some(Decimal::ZERO));
+        let big = dec("10000000000000000000");
+        assert_eq!(big.checked_mul(big), None);
+        assert_eq!(dec("1").checked_div(dec("3")), Some(dec("0.3333333333")));
+        assert_eq!(dec("-2").checked_div(dec("3")), Some(dec("-0.6666666667")));
+        assert_eq!(dec("1").checked_div(Decimal::ZERO), None);
+        assert_eq!(big.checked_div(dec("0.0000000001")), None);
+    }
+
+    #[test]
+    fn to_fixed_pads_and_rounds() {
+        assert_eq!(dec("20").to_fixed(2), "20.00");
+        assert_eq!(dec("19.995").to_fixed(2), "20.00");
+        assert_eq!(dec("-2.345").to_fixed(2), "-2.35");
+        assert_eq!(dec("-0.001").to_fixed(2), "0.00");
+        assert_eq!(dec("2.5").to_fixed(0), "3");
+        assert_eq!(dec("1.5").to_fixed(12), "1.5000000000");
+    }
+}
@@ -0,0 +1,58 @@
+use std::error::Error;
+use std::fmt;
+
+// The error object a CATCH block receives. System errors carry the numbered
+// runtime messages; application errors raised by RETURN ERROR carry the
+// returned value instead.
+#[derive(Debug, Clone)]
+pub struct AblError {
+    pub messages: Vec<String>,
+    pub return_value: Option<String>,
+}
+
+impl AblError {
+    pub fn app(return_value: String) -> Self {
+        AblError { messages: Vec::new(), return_value: Some(return_value) }
+    }
+
+    pub fn from_error(error: &(dyn Error + 'static)) -> Self {
+        match error.downcast_ref::<AblError>() {
+            Some(e) => e.clone(),
+            None => AblError { messages: vec![error.to_string()], return_value: None },
+        }
+    }
+
+    pub fn is_app(&self) -> bool {
+        self.return_value.is_some()
+    }
+
+    pub fn message(&self, index: i64) -> String {
+        usize::try_from(index - 1)
+            .ok()
+            .and_then(|i| self.messages.get(i))
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    // Messages end with their number in parentheses, e.g. "... (91)".
+    pub fn message_number(&self, index: i64) -> i64 {
+        let message = self.message(index);
+        message
+            .trim_end()
+            .strip_suffix(')')
+            .and_then(|m| m.rsplit_once('('))
+            .and_then(|(_, n)| n.parse().ok())
+            .unwrap_or(0)
+    }
+}
+
+impl fmt::Display for AblError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (&self.return_value, self.messages.is_empty()) {
+            (Some(value), true) => write!(f, "{}", value),
+            _ => write!(f, "{}", self.messages.join("\n")),
+        }
+    }
+}
+
+impl Error for AblError {}
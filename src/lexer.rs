@@ -1,10 +1,32 @@
+use crate::decimal::Decimal;
+
 #[derive(Debug, Clone)]
 pub enum Token {
-    ForEach,
-    FindFirst,
+    For,
+    Find,
+    First,
+    Last,
+    Prev,
     Where,
     Create,
     Delete,
+    Assign,
+    Define,
+    Variable,
+    As,
+    NoUndo,
+    Initial,
+    If,
+    Then,
+    Else,
+    Do,
+    End,
+    Repeat,
+    While,
+    To,
+    By,
+    Leave,
+    Next,
     Display,
     
     Identifier(String),
@@ -23,7 +45,54 @@ pub enum Token {
     Colon,
     LParen,
     RParen,
-    Float(f64),
+    Decimal(Decimal),
+    Date([i64; 3]),
+    Logical(bool),
+    Unknown,
+    Plus,
+    Minus,
+    Star,
+    Slash,
+    Modulo,
+    Not,
+    Begins,
+    Matches,
+    Contains,
+    UseIndex,
+    Descending,
+    Ascending,
+    Break,
+    FirstOf,
+    LastOf,
+    Accumulate,
+    Accum,
+    Each,
+    Of,
+    OuterJoin,
+    Comma,
+    NoError,
+    Available,
+    CanFind,
+    Transaction,
+    Undo,
+    Retry,
+    Catch,
+    Finally,
+    Throw,
+    Return,
+    Error,
+    On,
+    BlockLevel,
+    Procedure,
+    Run,
+    Input,
+    Output,
+    InputOutput,
+    Parameter,
+    Function,
+    Returns,
+    Forward,
+    Decimals,
     
     Eof,
 }
@@ -50,6 +119,11 @@ pub fn lex(source: &str) -> Vec<Token> {
                     buf.push('/');
                 }
             }
+            '+' | '*' => {
+                push_token_from_buf(&mut tokens, &mut buf);
+                tokens.push(if c == '+' { Token::Plus } else { Token::Star });
+                chars.next();
+            }
             '(' => {
                 push_token_from_buf(&mut tokens, &mut buf);
                 tokens.push(Token::LParen);
@@ -60,10 +134,19 @@ pub fn lex(source: &str) -> Vec<Token> {
                 tokens.push(Token::RParen);
                 chars.next();
             }
-            ' ' | '\n' | '\r' | '\t' | ',' => {
+            ',' => {
+                push_token_from_buf(&mut tokens, &mut buf);
+                tokens.push(Token::Comma);
+                chars.next();
+            }
+            ' ' | '\n' | '\r' | '\t' => {
                 push_token_from_buf(&mut tokens, &mut buf);
                 chars.next();
             }
+            '.' | ':' if !buf.is_empty() && is_word_char(chars.clone().nth(1)) => {
+                buf.push(c);
+                chars.next();
+            }
             '.' | ':' | '=' | '<' | '>' => {
                 push_token_from_buf(&mut tokens, &mut buf);
                 let mut op = c.to_string();
@@ -111,31 +194,54 @@ pub fn lex(source: &str) -> Vec<Token> {
     tokens
 }
 
+fn is_word_char(c: Option<char>) -> bool {
+    matches!(c, Some(c) if c.is_alphanumeric() || c == '_' || c == '-')
+}
+
 fn push_token_from_buf(tokens: &mut Vec<Token>, buf: &mut String) {
     let word = buf.trim();
     if word.is_empty() {
         return;
     }
 
+    // After an operand a leading `-` is subtraction, so `i -1` is `i - 1`
+    // rather than `i` followed by the literal -1.
+    if let Some(rest) = word.strip_prefix('-').filter(|r| !r.is_empty() && follows_operand(tokens)) {
+        let mut rest = rest.to_string();
+        tokens.push(Token::Minus);
+        push_token_from_buf(tokens, &mut rest);
+        buf.clear();
+        return;
+    }
+
     let upper = word.to_uppercase();
     match upper.as_str() {
-        "FOR EACH" => tokens.push(Token::ForEach),
-        "FOR" => {
-            *buf = "FOR ".to_string();
-            return;
-        }
-        "FIND FIRST" => tokens.push(Token::FindFirst),
-        "FIND" => {
-            *buf = "FIND ".to_string();
-            return;
-        }
-        "FIRST" if buf.starts_with("FIND ") => {
-            tokens.push(Token::FindFirst);
-        }
+        "FOR" => tokens.push(Token::For),
+        "FIND" => tokens.push(Token::Find),
+        "FIRST" => tokens.push(Token::First),
+        "LAST" => tokens.push(Token::Last),
+        "PREV" | "PREVIOUS" => tokens.push(Token::Prev),
         "WHERE" => tokens.push(Token::Where),
         "DISPLAY" => tokens.push(Token::Display),
         "CREATE" => tokens.push(Token::Create),
         "DELETE" => tokens.push(Token::Delete),
+        "ASSIGN" => tokens.push(Token::Assign),
+        "DEFINE" | "DEF" => tokens.push(Token::Define),
+        "VARIABLE" | "VAR" => tokens.push(Token::Variable),
+        "AS" => tokens.push(Token::As),
+        "NO-UNDO" => tokens.push(Token::NoUndo),
+        "INITIAL" | "INIT" => tokens.push(Token::Initial),
+        "IF" => tokens.push(Token::If),
+        "THEN" => tokens.push(Token::Then),
+        "ELSE" => tokens.push(Token::Else),
+        "DO" => tokens.push(Token::Do),
+        "END" => tokens.push(Token::End),
+        "REPEAT" => tokens.push(Token::Repeat),
+        "WHILE" => tokens.push(Token::While),
+        "TO" => tokens.push(Token::To),
+        "BY" => tokens.push(Token::By),
+        "LEAVE" => tokens.push(Token::Leave),
+        "NEXT" => tokens.push(Token::Next),
         "AND" => tokens.push(Token::And),
         "OR" => tokens.push(Token::Or),
         "EQ" => tokens.push(Token::Equals),
@@ -144,15 +250,87 @@ fn push_token_from_buf(tokens: &mut Vec<Token>, buf: &mut String) {
         "GT" => tokens.push(Token::GreaterThan),
         "LE" => tokens.push(Token::LessOrEqual),
         "GE" => tokens.push(Token::GreaterOrEqual),
+        "-" => tokens.push(Token::Minus),
+        "/" => tokens.push(Token::Slash),
+        "MODULO" => tokens.push(Token::Modulo),
+        "NOT" => tokens.push(Token::Not),
+        "BEGINS" => tokens.push(Token::Begins),
+        "MATCHES" => tokens.push(Token::Matches),
+        "CONTAINS" => tokens.push(Token::Contains),
+        "USE-INDEX" => tokens.push(Token::UseIndex),
+        "DESCENDING" | "DESC" => tokens.push(Token::Descending),
+        "ASCENDING" => tokens.push(Token::Ascending),
+        "BREAK" => tokens.push(Token::Break),
+        "FIRST-OF" => tokens.push(Token::FirstOf),
+        "LAST-OF" => tokens.push(Token::LastOf),
+        "ACCUMULATE" => tokens.push(Token::Accumulate),
+        "ACCUM" => tokens.push(Token::Accum),
+        "EACH" => tokens.push(Token::Each),
+        "OF" => tokens.push(Token::Of),
+        "OUTER-JOIN" => tokens.push(Token::OuterJoin),
+        "NO-ERROR" => tokens.push(Token::NoError),
+        "AVAILABLE" | "AVAIL" => tokens.push(Token::Available),
+        "CAN-FIND" => tokens.push(Token::CanFind),
+        "TRANSACTION" => tokens.push(Token::Transaction),
+        "UNDO" => tokens.push(Token::Undo),
+        "RETRY" => tokens.push(Token::Retry),
+        "CATCH" => tokens.push(Token::Catch),
+        "FINALLY" => tokens.push(Token::Finally),
+        "THROW" => tokens.push(Token::Throw),
+        "RETURN" => tokens.push(Token::Return),
+        "ERROR" => tokens.push(Token::Error),
+        "ON" => tokens.push(Token::On),
+        "BLOCK-LEVEL" | "ROUTINE-LEVEL" => tokens.push(Token::BlockLevel),
+        "PROCEDURE" => tokens.push(Token::Procedure),
+        "RUN" => tokens.push(Token::Run),
+        "INPUT" => tokens.push(Token::Input),
+        "OUTPUT" => tokens.push(Token::Output),
+        "INPUT-OUTPUT" => tokens.push(Token::InputOutput),
+        "PARAMETER" | "PARAM" => tokens.push(Token::Parameter),
+        "FUNCTION" => tokens.push(Token::Function),
+        "RETURNS" => tokens.push(Token::Returns),
+        "FORWARD" => tokens.push(Token::Forward),
+        "DECIMALS" => tokens.push(Token::Decimals),
+        "TRUE" | "YES" => tokens.push(Token::Logical(true)),
+        "FALSE" | "NO" => tokens.push(Token::Logical(false)),
+        "?" => tokens.push(Token::Unknown),
         _ => {
             if let Ok(n) = word.parse::<i64>() {
                 tokens.push(Token::Number(n));
-            } else if let Ok(f) = word.parse::<f64>() {
-                tokens.push(Token::Float(f));
+            } else if let Some(d) = Decimal::parse(word) {
+                tokens.push(Token::Decimal(d));
+            } else if let Some(parts) = date_literal(word) {
+                tokens.push(Token::Date(parts));
+            } else if let Some(rest) = word.strip_prefix('-').filter(|r| !r.is_empty()) {
+                let mut rest = rest.to_string();
+                tokens.push(Token::Minus);
+                push_token_from_buf(tokens, &mut rest);
             } else {
                 tokens.push(Token::Identifier(word.to_string()));
             }
         }
     }
     buf.clear();
-}
\ No newline at end of file
+}
+
+fn follows_operand(tokens: &[Token]) -> bool {
+    matches!(
+        tokens.last(),
+        Some(
+            Token::Identifier(_)
+                | Token::StringLit(_)
+                | Token::Number(_)
+                | Token::Decimal(_)
+                | Token::Date(_)
+                | Token::Logical(_)
+                | Token::Unknown
+                | Token::RParen
+        )
+    )
+}
+
+// Three numbers separated by slashes, such as 12/31/2025.
+fn date_literal(word: &str) -> Option<[i64; 3]> {
+    let parts: Vec<i64> = word.split('/').map(|p| p.parse().ok()).collect::<Option<_>>()?;
+    parts.try_into().ok()
+}
@@ -1,24 +1,40 @@
 use std::env;
 use std::fs;
+use std::path::{Path, PathBuf};
 use std::process;
 
 mod lexer;
 mod parser;
+mod accum;
 mod ast;
+mod builtins;
+mod date;
+mod decimal;
+mod error;
 mod runtime;
 mod sqlgen;
 mod tui;
+mod value;
 
 fn main() {
-    let args: Vec<String> = env::args().collect();
+    let mut args = env::args().skip(1);
     
     let mut db_path = None;
     let mut script_path = None;
+    let mut propath = None;
     let mut verbose = false;
 
-    for arg in args.iter().skip(1) {
+    while let Some(arg) = args.next() {
         if arg == "-v" || arg == "--verbose" {
             verbose = true;
+        } else if arg == "-p" || arg == "--propath" {
+            propath = args.next();
+        } else if arg == "-d" || arg == "--date-format" {
+            let format = args.next().unwrap_or_default();
+            if let Err(err) = date::set_format(&format) {
+                eprintln!("{}", err);
+                process::exit(1);
+            }
         } else if db_path.is_none() {
             db_path = Some(arg);
         } else if script_path.is_none() {
@@ -27,20 +43,27 @@ fn main() {
     }
     
     if db_path.is_none() || script_path.is_none() {
-        eprintln!("Usage: liteabl <database.db> <script.p> [--verbose]");
+        eprintln!("Usage: liteabl <database.db> <script.p> [--propath dir,dir...] [--date-format mdy] [--verbose]");
         process::exit(1);
     }
     
     let db_path = db_path.unwrap();
     let script_path = script_path.unwrap();
     
-    let source = fs::read_to_string(script_path)
+    // Like PROPATH, a comma-separated list of directories searched in order
+    // for RUN of an external program; by default the script's own directory.
+    let propath: Vec<PathBuf> = match propath {
+        Some(dirs) => dirs.split(',').map(PathBuf::from).collect(),
+        None => vec![Path::new(&script_path).parent().unwrap_or(Path::new("")).to_path_buf()],
+    };
+    
+    let source = fs::read_to_string(&script_path)
         .unwrap_or_else(|err| {
             eprintln!("Error reading script: {}", err);
             process::exit(1);
         });
     
-    match runtime::execute(db_path, &source, verbose) {
+    match runtime::execute(&db_path, &source, &propath, verbose) {
         Ok(_) => println!("Script executed successfully"),
         Err(e) => {
             eprintln!("Error: {}", e);
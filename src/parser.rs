@@ -1,14 +1,32 @@
+use std::collections::HashSet;
+use crate::accum::keyword;
+use crate::builtins;
 use crate::lexer::Token;
-use crate::ast::{Statement, Expr, Op};
+use crate::ast::{Accumulation, Aggregate, DataType, FieldRef, FindMode, Iteration, ParameterMode, RecordPhrase, SortKey, Statement, Expr, Op, UnaryOp, UndoAction};
 
 pub struct Parser {
 	tokens: Vec<Token>,
 	pos: usize,
+	// Functions declared so far; only these and the built-in functions are
+	// parsed as calls.
+	functions: HashSet<String>,
+	// What ACCUMULATE statements collect and what ACCUM expressions ask for,
+	// checked against each other once the whole file is parsed.
+	accumulated: Vec<(String, Aggregate)>,
+	accum_refs: Vec<(String, Aggregate)>,
+	errors: Vec<String>,
 }
 
 impl Parser {
 	pub fn new(tokens: Vec<Token>) -> Self {
-		Parser { tokens, pos: 0 }
+		Parser {
+			tokens,
+			pos: 0,
+			functions: HashSet::new(),
+			accumulated: Vec::new(),
+			accum_refs: Vec::new(),
+			errors: Vec::new(),
+		}
 	}
 
 	fn peek(&self) -> &Token {
@@ -21,40 +39,81 @@ impl Parser {
 		tok
 	}
 
-	pub fn parse_statements(&mut self) -> Vec<Statement> {
+	pub fn parse_statements(&mut self) -> Result<Vec<Statement>, String> {
 		let mut stmts = Vec::new();
 		while !matches!(self.peek(), Token::Eof) {
-			match self.peek() {
-				Token::ForEach | Token::FindFirst | Token::Create | Token::Delete => {
-					if let Some(stmt) = self.parse_statement() {
-						stmts.push(stmt);
-					} else {
-						self.next();
-					}
-				}
-				Token::Display => {
-					self.next();
-					while !matches!(self.peek(), Token::Dot | Token::Eof) {
-						self.next();
-					}
-					if let Token::Dot = self.peek() {
-						self.next();
-					}
-				}
-				_ => {
-					self.next();
-				}
+			self.parse_into(&mut stmts);
+		}
+		for (name, aggregate) in &self.accum_refs {
+			if !self.accumulated.contains(&(name.clone(), *aggregate)) {
+				self.errors.push(format!("** ACCUM {} {} has no ACCUMULATE statement collecting it.", keyword(*aggregate), name));
+			}
+		}
+		match self.errors.first() {
+			Some(error) => Err(error.clone()),
+			None => Ok(stmts),
+		}
+	}
+
+	// Parses one statement, or records where parsing failed and skips past
+	// the offending token so later errors are still found.
+	fn parse_into(&mut self, stmts: &mut Vec<Statement>) {
+		let start = self.pos;
+		match self.parse_statement() {
+			Some(stmt) => stmts.push(stmt),
+			None => {
+				let at = if self.pos > start { self.pos - 1 } else { self.pos };
+				let token = self.tokens.get(at).unwrap_or(&Token::Eof);
+				self.errors.push(format!("** Unable to understand after -- \"{}\". (247)", describe(token)));
+				self.pos = at + 1;
 			}
 		}
-		stmts
 	}
 
 	fn parse_statement(&mut self) -> Option<Statement> {
 		match self.peek() {
-			Token::ForEach => self.parse_foreach(),
-			Token::FindFirst => self.parse_findfirst(),
+			Token::For if matches!(self.tokens.get(self.pos + 1), Some(Token::Each)) => self.parse_foreach(),
+			Token::Find => self.parse_find(),
 			Token::Create => self.parse_create(),
 			Token::Delete => self.parse_delete(),
+			Token::Define => self.parse_define(),
+			Token::If => self.parse_if(),
+			Token::Do => self.parse_do(),
+			Token::Repeat => self.parse_repeat(),
+			Token::Accumulate => self.parse_accumulate(),
+			Token::Undo => self.parse_undo(),
+			Token::Catch => self.parse_catch(),
+			Token::Finally => {
+				self.next();
+				let body = self.parse_block();
+				Some(Statement::Finally { body })
+			}
+			Token::Return => self.parse_return(),
+			Token::Procedure => self.parse_procedure(),
+			Token::Run => self.parse_run(),
+			Token::Function => self.parse_function(),
+			// Errors already propagate out of every block as if each were
+			// declared ON ERROR UNDO, THROW, so the directive is only skipped.
+			Token::BlockLevel => {
+				while !matches!(self.next(), Token::Dot | Token::Eof) {}
+				self.parse_statement()
+			}
+			Token::Leave => {
+				self.next();
+				let label = self.parse_label_ref();
+				Some(Statement::Leave { label })
+			}
+			Token::Next => {
+				self.next();
+				let label = self.parse_label_ref();
+				Some(Statement::Next { label })
+			}
+			Token::Identifier(_) if matches!(self.tokens.get(self.pos + 1), Some(Token::Colon)) => self.parse_labeled(),
+			Token::Assign => {
+				self.next();
+				self.parse_assign()
+			}
+			Token::Identifier(_) if matches!(self.tokens.get(self.pos + 1), Some(Token::Equals)) => self.parse_assign(),
 			Token::Display => self.parse_display(),
 			_ => None,
 		}
@@ -62,69 +121,378 @@ impl Parser {
 
 	fn parse_foreach(&mut self) -> Option<Statement> {
 		self.next();
+		self.next();
+		let mut phrases = vec![self.parse_record_phrase()?];
+		while let (Token::Comma, Some(Token::Each)) = (self.peek(), self.tokens.get(self.pos + 1)) {
+			self.next();
+			self.next();
+			phrases.push(self.parse_record_phrase()?);
+		}
+		let mut sort = Vec::new();
+		let mut breaks = false;
+		loop {
+			match self.peek() {
+				Token::Break => {
+					self.next();
+					breaks = true;
+				}
+				Token::By => {
+					self.next();
+					let expr = self.parse_expr()?;
+					let descending = matches!(self.peek(), Token::Descending);
+					if matches!(self.peek(), Token::Descending | Token::Ascending) {
+						self.next();
+					}
+					sort.push(SortKey { expr, descending });
+				}
+				_ => break,
+			}
+		}
+		let body = self.parse_block();
+		Some(Statement::ForEach {
+			label: None,
+			phrases,
+			sort,
+			breaks,
+			body,
+		})
+	}
+
+	fn parse_record_phrase(&mut self) -> Option<RecordPhrase> {
 		let table = if let Token::Identifier(name) = self.next() {
 			name.clone()
 		} else {
 			return None;
 		};
-		let mut where_clause = None;
-		if let Token::Where = self.peek() {
-			self.next();
-			where_clause = self.parse_expr();
+		let mut phrase = RecordPhrase {
+			table,
+			of: None,
+			where_clause: None,
+			use_index: None,
+			outer_join: false,
+		};
+		loop {
+			match self.peek() {
+				Token::Of => {
+					self.next();
+					if let Token::Identifier(name) = self.next() {
+						phrase.of = Some(name.clone());
+					}
+				}
+				Token::Where => {
+					self.next();
+					phrase.where_clause = self.parse_expr();
+				}
+				Token::UseIndex => {
+					self.next();
+					if let Token::Identifier(name) = self.next() {
+						phrase.use_index = Some(name.clone());
+					}
+				}
+				Token::OuterJoin => {
+					self.next();
+					phrase.outer_join = true;
+				}
+				_ => break,
+			}
 		}
+		Some(phrase)
+	}
+
+	fn parse_block(&mut self) -> Vec<Statement> {
 		if let Token::Colon = self.peek() {
 			self.next();
 		}
 		let mut body = Vec::new();
-		while !matches!(self.peek(), Token::Identifier(s) if s == "END") && !matches!(self.peek(), Token::Eof) {
-			if let Some(stmt) = self.parse_statement() {
-				body.push(stmt);
-			} else {
+		while !matches!(self.peek(), Token::End | Token::Eof) {
+			self.parse_into(&mut body);
+		}
+		if let Token::End = self.peek() {
+			self.next();
+			if matches!(self.peek(), Token::Catch | Token::Finally | Token::Procedure | Token::Function) {
 				self.next();
 			}
+			if let Token::Dot = self.peek() {
+				self.next();
+			}
+		}
+		body
+	}
+
+	fn parse_if(&mut self) -> Option<Statement> {
+		self.next();
+		let condition = self.parse_expr()?;
+		if !matches!(self.next(), Token::Then) {
+			return None;
 		}
-		if let Token::Identifier(s) = self.peek() {
-			if s == "END" {
+		let then_branch = Box::new(self.parse_statement()?);
+		let mut else_branch = None;
+		if let Token::Else = self.peek() {
+			self.next();
+			else_branch = Some(Box::new(self.parse_statement()?));
+		}
+		Some(Statement::If {
+			condition,
+			then_branch,
+			else_branch,
+		})
+	}
+
+	fn parse_do(&mut self) -> Option<Statement> {
+		self.next();
+		let mut iteration = None;
+		if let (Token::Identifier(name), Some(Token::Equals)) = (self.peek(), self.tokens.get(self.pos + 1)) {
+			let variable = FieldRef::parse(name);
+			self.next();
+			self.next();
+			let from = self.parse_expr()?;
+			if !matches!(self.next(), Token::To) {
+				return None;
+			}
+			let to = self.parse_expr()?;
+			let mut by = None;
+			if let Token::By = self.peek() {
 				self.next();
-				if let Token::Dot = self.peek() {
+				by = Some(self.parse_expr()?);
+			}
+			iteration = Some(Box::new(Iteration { variable, from, to, by }));
+		}
+		let condition = self.parse_while()?;
+		let mut transaction = false;
+		let mut on_error = None;
+		loop {
+			match self.peek() {
+				Token::Transaction => {
 					self.next();
+					transaction = true;
 				}
+				Token::On => {
+					self.next();
+					if !matches!(self.next(), Token::Error) || !matches!(self.next(), Token::Undo) {
+						return None;
+					}
+					on_error = Some(self.parse_undo_action()?);
+				}
+				_ => break,
 			}
 		}
-		Some(Statement::ForEach {
-			table,
-			where_clause,
+		let body = self.parse_block();
+		Some(Statement::Do {
+			label: None,
+			iteration,
+			condition,
+			transaction,
+			on_error,
 			body,
 		})
 	}
 
-	fn parse_findfirst(&mut self) -> Option<Statement> {
+	fn parse_repeat(&mut self) -> Option<Statement> {
+		self.next();
+		let condition = self.parse_while()?;
+		let body = self.parse_block();
+		Some(Statement::Repeat {
+			label: None,
+			condition,
+			body,
+		})
+	}
+
+	fn parse_while(&mut self) -> Option<Option<Expr>> {
+		if let Token::While = self.peek() {
+			self.next();
+			return self.parse_expr().map(Some);
+		}
+		Some(None)
+	}
+
+	fn parse_accumulate(&mut self) -> Option<Statement> {
+		self.next();
+		let mut items = Vec::new();
+		while !matches!(self.peek(), Token::Dot | Token::Eof) {
+			let expr = self.parse_expr()?;
+			if !matches!(self.next(), Token::LParen) {
+				return None;
+			}
+			let mut aggregates = Vec::new();
+			let mut groups = Vec::new();
+			loop {
+				match self.next().clone() {
+					Token::Identifier(name) => aggregates.push(parse_aggregate(&name)?),
+					Token::By => {
+						if let Token::Identifier(group) = self.next() {
+							groups.push(group.clone());
+						}
+					}
+					Token::RParen => break,
+					_ => return None,
+				}
+			}
+			let name = accum_name(&expr);
+			self.accumulated.extend(aggregates.iter().map(|a| (name.clone(), *a)));
+			items.push(Accumulation { expr, aggregates, groups });
+		}
+		if let Token::Dot = self.peek() {
+			self.next();
+		}
+		Some(Statement::Accumulate { items })
+	}
+
+	fn parse_labeled(&mut self) -> Option<Statement> {
+		let name = if let Token::Identifier(name) = self.next() {
+			name.clone()
+		} else {
+			return None;
+		};
+		self.next();
+		let mut stmt = self.parse_statement()?;
+		match &mut stmt {
+			Statement::ForEach { label, .. } | Statement::Do { label, .. } | Statement::Repeat { label, .. } => {
+				*label = Some(name);
+			}
+			_ => {}
+		}
+		Some(stmt)
+	}
+
+	fn parse_undo(&mut self) -> Option<Statement> {
+		self.next();
+		let label = self.parse_name();
+		let action = self.parse_undo_action()?;
+		if let Token::Dot = self.peek() {
+			self.next();
+		}
+		Some(Statement::Undo { label, action })
+	}
+
+	fn parse_undo_action(&mut self) -> Option<UndoAction> {
+		if !matches!(self.peek(), Token::Comma) {
+			return Some(UndoAction::Leave(None));
+		}
+		self.next();
+		let action = match self.next() {
+			Token::Leave => UndoAction::Leave(self.parse_name()),
+			Token::Next => UndoAction::Next(self.parse_name()),
+			Token::Retry => {
+				self.parse_name();
+				UndoAction::Retry
+			}
+			Token::Throw => UndoAction::Throw(self.parse_name()),
+			_ => return None,
+		};
+		Some(action)
+	}
+
+	fn parse_name(&mut self) -> Option<String> {
+		if let Token::Identifier(name) = self.peek() {
+			let name = name.clone();
+			self.next();
+			return Some(name);
+		}
+		None
+	}
+
+	fn parse_catch(&mut self) -> Option<Statement> {
 		self.next();
+		let variable = self.parse_name()?;
+		if !matches!(self.next(), Token::As) {
+			return None;
+		}
+		let class = self.parse_name()?;
+		let body = self.parse_block();
+		Some(Statement::Catch { variable, class, body })
+	}
+
+	fn parse_return(&mut self) -> Option<Statement> {
+		self.next();
+		let error = matches!(self.peek(), Token::Error);
+		if error {
+			self.next();
+		}
+		let value = self.parse_expr();
+		if let Token::Dot = self.peek() {
+			self.next();
+		}
+		Some(Statement::Return { error, value })
+	}
+
+	fn parse_label_ref(&mut self) -> Option<String> {
+		let label = if let Token::Identifier(name) = self.peek() {
+			Some(name.clone())
+		} else {
+			None
+		};
+		if label.is_some() {
+			self.next();
+		}
+		if let Token::Dot = self.peek() {
+			self.next();
+		}
+		label
+	}
+
+	fn parse_find(&mut self) -> Option<Statement> {
+		self.next();
+		let mode = match self.peek() {
+			Token::First => FindMode::First,
+			Token::Last => FindMode::Last,
+			Token::Next => FindMode::Next,
+			Token::Prev => FindMode::Prev,
+			_ => FindMode::Unique,
+		};
+		if mode != FindMode::Unique {
+			self.next();
+		}
 		let table = if let Token::Identifier(name) = self.next() {
 			name.clone()
 		} else {
 			return None;
 		};
+		let key = match self.peek() {
+			Token::Number(_) | Token::StringLit(_) | Token::LParen => self.parse_primary(),
+			_ => None,
+		};
 		let mut where_clause = None;
 		if let Token::Where = self.peek() {
 			self.next();
 			where_clause = self.parse_expr();
 		}
-		if let Token::Colon = self.peek() {
-			self.next();
-		}
-		if let Token::Identifier(s) = self.peek() {
-			if s == "END" {
+		let no_error = self.parse_no_error();
+		let find = Statement::Find {
+			mode,
+			table,
+			key,
+			where_clause,
+			no_error,
+		};
+		match self.peek() {
+			// `FIND ...: ... END.` runs the block once after the FIND.
+			Token::Colon => {
+				let mut body = vec![find];
+				body.extend(self.parse_block());
+				Some(Statement::Do {
+					label: None,
+					iteration: None,
+					condition: None,
+					transaction: false,
+					on_error: None,
+					body,
+				})
+			}
+			Token::Dot => {
 				self.next();
-				if let Token::Dot = self.peek() {
-					self.next();
-				}
+				Some(find)
 			}
+			_ => Some(find),
 		}
-		Some(Statement::FindFirst {
-			table,
-			where_clause,
-		})
+	}
+
+	fn parse_no_error(&mut self) -> bool {
+		let no_error = matches!(self.peek(), Token::NoError);
+		if no_error {
+			self.next();
+		}
+		no_error
 	}
 
 	fn parse_create(&mut self) -> Option<Statement> {
@@ -134,10 +502,11 @@ impl Parser {
 		} else {
 			return None;
 		};
+		let no_error = self.parse_no_error();
 		if let Token::Dot = self.peek() {
 			self.next();
 		}
-		Some(Statement::Create { table })
+		Some(Statement::Create { table, no_error })
 	}
 
 	fn parse_delete(&mut self) -> Option<Statement> {
@@ -147,34 +516,53 @@ impl Parser {
 		} else {
 			return None;
 		};
+		let no_error = self.parse_no_error();
 		if let Token::Dot = self.peek() {
 			self.next();
 		}
-		Some(Statement::Delete { table })
+		Some(Statement::Delete { table, no_error })
 	}
 
-	fn parse_display(&mut self) -> Option<Statement> {
+	fn parse_define(&mut self) -> Option<Statement> {
 		self.next();
-		let mut fields = Vec::new();
+		let mode = match self.next() {
+			Token::Variable => None,
+			Token::Input => Some(ParameterMode::Input),
+			Token::Output => Some(ParameterMode::Output),
+			Token::InputOutput => Some(ParameterMode::InputOutput),
+			_ => return None,
+		};
+		if mode.is_some() && !matches!(self.next(), Token::Parameter) {
+			return None;
+		}
+		let name = if let Token::Identifier(name) = self.next() {
+			name.clone()
+		} else {
+			return None;
+		};
+		let mut data_type = None;
+		let mut initial = None;
+		let mut decimals = None;
 		loop {
 			match self.peek() {
-				Token::Identifier(name) => {
-					let name = name.clone();
+				Token::As => {
 					self.next();
-					fields.push(name);
+					if let Token::Identifier(ty) = self.next() {
+						data_type = parse_data_type(ty);
+					}
 				}
-				Token::StringLit(s) => {
-					let s = format!("\"{}\"", s);
+				Token::NoUndo => {
 					self.next();
-					fields.push(s);
 				}
-				Token::Number(n) => {
-					fields.push(n.to_string());
+				Token::Initial => {
 					self.next();
+					initial = self.parse_primary();
 				}
-				Token::Float(f) => {
-					fields.push(f.to_string());
+				Token::Decimals => {
 					self.next();
+					if let Token::Number(n) = self.next() {
+						decimals = u32::try_from(*n).ok();
+					}
 				}
 				_ => break,
 			}
@@ -182,14 +570,158 @@ impl Parser {
 		if let Token::Dot = self.peek() {
 			self.next();
 		}
-		Some(Statement::Display { fields })
+		if let Some(mode) = mode {
+			return Some(Statement::DefineParameter { mode, name, data_type: data_type? });
+		}
+		Some(Statement::DefineVariable {
+			name,
+			data_type: data_type?,
+			initial,
+			decimals,
+		})
+	}
+
+	fn parse_procedure(&mut self) -> Option<Statement> {
+		self.next();
+		let name = self.parse_name()?;
+		let body = self.parse_block();
+		Some(Statement::Procedure { name, body })
+	}
+
+	// FUNCTION name RETURNS type [( [INPUT] param AS type, ... )] followed by
+	// either FORWARD or the body. The name is declared before the body is
+	// parsed so that the function can call itself.
+	fn parse_function(&mut self) -> Option<Statement> {
+		self.next();
+		let name = self.parse_name()?;
+		if !matches!(self.next(), Token::Returns) {
+			return None;
+		}
+		let returns = match self.next() {
+			Token::Identifier(ty) => parse_data_type(ty)?,
+			_ => return None,
+		};
+		let mut params = Vec::new();
+		if let Token::LParen = self.peek() {
+			self.next();
+			while !matches!(self.peek(), Token::RParen | Token::Eof) {
+				if let Token::Input = self.peek() {
+					self.next();
+				}
+				let param = self.parse_name()?;
+				if !matches!(self.next(), Token::As) {
+					return None;
+				}
+				let data_type = match self.next() {
+					Token::Identifier(ty) => parse_data_type(ty)?,
+					_ => return None,
+				};
+				params.push((param, data_type));
+				if let Token::Comma = self.peek() {
+					self.next();
+				}
+			}
+			self.next();
+		}
+		self.functions.insert(name.to_uppercase());
+		let body = if let Token::Forward = self.peek() {
+			self.next();
+			if let Token::Dot = self.peek() {
+				self.next();
+			}
+			None
+		} else {
+			let body = self.parse_block();
+			if !body.iter().all(function_statement) {
+				self.errors.push(format!(
+					"** FUNCTION {} can only use DEFINE VARIABLE, assignment, IF, DO, DO WHILE and RETURN statements.",
+					name
+				));
+			}
+			Some(body)
+		};
+		Some(Statement::Function { name, returns, params, body })
+	}
+
+	// RUN name [( [INPUT | OUTPUT | INPUT-OUTPUT] expr, ... )].
+	fn parse_run(&mut self) -> Option<Statement> {
+		self.next();
+		let name = match self.next() {
+			Token::Identifier(name) | Token::StringLit(name) => name.clone(),
+			_ => return None,
+		};
+		let mut args = Vec::new();
+		if let Token::LParen = self.peek() {
+			self.next();
+			while !matches!(self.peek(), Token::RParen | Token::Eof) {
+				let mode = match self.peek() {
+					Token::Output => ParameterMode::Output,
+					Token::InputOutput => ParameterMode::InputOutput,
+					_ => ParameterMode::Input,
+				};
+				if matches!(self.peek(), Token::Input | Token::Output | Token::InputOutput) {
+					self.next();
+				}
+				args.push((mode, self.parse_expr()?));
+				if let Token::Comma = self.peek() {
+					self.next();
+				}
+			}
+			self.next();
+		}
+		if let Token::Dot = self.peek() {
+			self.next();
+		}
+		Some(Statement::Run { name, args })
+	}
+
+	fn parse_assign(&mut self) -> Option<Statement> {
+		let mut assignments = Vec::new();
+		while let Token::Identifier(target) = self.peek() {
+			let target = FieldRef::parse(target);
+			if !matches!(self.tokens.get(self.pos + 1), Some(Token::Equals)) {
+				break;
+			}
+			self.next();
+			self.next();
+			let value = self.parse_expr()?;
+			assignments.push((target, value));
+		}
+		let no_error = self.parse_no_error();
+		if let Token::Dot = self.peek() {
+			self.next();
+		}
+		if assignments.is_empty() {
+			return None;
+		}
+		Some(Statement::Assign { assignments, no_error })
+	}
+
+	fn parse_display(&mut self) -> Option<Statement> {
+		self.next();
+		let mut items = Vec::new();
+		while let Some(item) = self.parse_expr() {
+			items.push(item);
+		}
+		if let Token::Dot = self.peek() {
+			self.next();
+		}
+		Some(Statement::Display { items })
 	}
 
 	fn parse_expr(&mut self) -> Option<Expr> {
-		let mut left = self.parse_primary()?;
+		self.parse_binary(0)
+	}
+
+	fn parse_binary(&mut self, min_prec: u8) -> Option<Expr> {
+		let mut left = self.parse_unary()?;
 		while let Some(op) = self.peek_op() {
+			let prec = precedence(&op);
+			if prec < min_prec {
+				break;
+			}
 			self.next();
-			let right = self.parse_primary()?;
+			let right = self.parse_binary(prec + 1)?;
 			left = Expr::BinOp {
 				left: Box::new(left),
 				op,
@@ -199,6 +731,26 @@ impl Parser {
 		Some(left)
 	}
 
+	fn parse_unary(&mut self) -> Option<Expr> {
+		match self.peek() {
+			Token::Minus => {
+				self.next();
+				let expr = self.parse_binary(UNARY_PREC)?;
+				Some(Expr::Unary { op: UnaryOp::Neg, expr: Box::new(expr) })
+			}
+			Token::Plus => {
+				self.next();
+				self.parse_binary(UNARY_PREC)
+			}
+			Token::Not => {
+				self.next();
+				let expr = self.parse_binary(precedence(&Op::Eq))?;
+				Some(Expr::Unary { op: UnaryOp::Not, expr: Box::new(expr) })
+			}
+			_ => self.parse_primary(),
+		}
+	}
+
 	fn parse_primary(&mut self) -> Option<Expr> {
 		match self.peek() {
 			Token::LParen => {
@@ -210,17 +762,102 @@ impl Parser {
 				Some(Expr::Group(Box::new(expr)))
 			}
 			Token::Identifier(name) => {
-				let mut full_name = name.clone();
+				let name = name.clone();
 				self.next();
-				if let Token::Dot = self.peek() {
-					if let Some(Token::Identifier(field)) = self.tokens.get(self.pos + 1) {
-						full_name.push('.');
-						full_name.push_str(field);
-						self.next();
-						self.next();
+				if let Some((handle, attribute)) = name.split_once(':') {
+					let args = if let Token::LParen = self.peek() {
+						self.parse_args()?
+					} else {
+						Vec::new()
+					};
+					return Some(Expr::Attribute { handle: handle.to_string(), name: attribute.to_string(), args });
+				}
+				if builtins::is_function(&name) && !builtins::takes_args(&name) {
+					return Some(Expr::Call { name, args: Vec::new() });
+				}
+				let function = self.functions.contains(&name.to_uppercase()) || builtins::is_function(&name);
+				if function && matches!(self.peek(), Token::LParen) {
+					let args = self.parse_args()?;
+					return Some(Expr::Call { name, args });
+				}
+				Some(Expr::Identifier(FieldRef::parse(&name)))
+			}
+			Token::Available => {
+				self.next();
+				let parens = matches!(self.peek(), Token::LParen);
+				if parens {
+					self.next();
+				}
+				let buffer = if let Token::Identifier(name) = self.next() {
+					name.clone()
+				} else {
+					return None;
+				};
+				if parens && !matches!(self.next(), Token::RParen) {
+					return None;
+				}
+				Some(Expr::Available(buffer))
+			}
+			Token::CanFind => {
+				self.next();
+				if !matches!(self.next(), Token::LParen) {
+					return None;
+				}
+				let mode = match self.peek() {
+					Token::First => FindMode::First,
+					Token::Last => FindMode::Last,
+					_ => FindMode::Unique,
+				};
+				if mode != FindMode::Unique {
+					self.next();
+				}
+				let table = if let Token::Identifier(name) = self.next() {
+					name.clone()
+				} else {
+					return None;
+				};
+				let mut where_clause = None;
+				if let Token::Where = self.peek() {
+					self.next();
+					where_clause = Some(Box::new(self.parse_expr()?));
+				}
+				if !matches!(self.next(), Token::RParen) {
+					return None;
+				}
+				Some(Expr::CanFind { mode, table, where_clause })
+			}
+			Token::FirstOf | Token::LastOf => {
+				let last = matches!(self.next(), Token::LastOf);
+				if !matches!(self.next(), Token::LParen) {
+					return None;
+				}
+				let group = if let Token::Identifier(name) = self.next() {
+					name.clone()
+				} else {
+					return None;
+				};
+				if !matches!(self.next(), Token::RParen) {
+					return None;
+				}
+				Some(if last { Expr::LastOf(group) } else { Expr::FirstOf(group) })
+			}
+			Token::Accum => {
+				self.next();
+				let aggregate = if let Token::Identifier(name) = self.next() {
+					parse_aggregate(name)?
+				} else {
+					return None;
+				};
+				let mut group = None;
+				if let Token::By = self.peek() {
+					self.next();
+					if let Token::Identifier(name) = self.next() {
+						group = Some(name.clone());
 					}
 				}
-				Some(Expr::Identifier(full_name))
+				let expr = self.parse_primary()?;
+				self.accum_refs.push((accum_name(&expr), aggregate));
+				Some(Expr::Accum { aggregate, group, expr: Box::new(expr) })
 			}
 			Token::StringLit(s) => {
 				let s = s.clone();
@@ -232,15 +869,44 @@ impl Parser {
 				self.next();
 				Some(Expr::Number(n))
 			}
-			Token::Float(f) => {
-				let f = *f;
+			Token::Decimal(d) => {
+				let d = *d;
+				self.next();
+				Some(Expr::Decimal(d))
+			}
+			Token::Date(parts) => {
+				let parts = *parts;
 				self.next();
-				Some(Expr::Float(f))
+				Some(Expr::Date(parts))
+			}
+			Token::Logical(b) => {
+				let b = *b;
+				self.next();
+				Some(Expr::Logical(b))
+			}
+			Token::Unknown => {
+				self.next();
+				Some(Expr::Unknown)
 			}
 			_ => None,
 		}
 	}
 
+	fn parse_args(&mut self) -> Option<Vec<Expr>> {
+		self.next();
+		let mut args = Vec::new();
+		while !matches!(self.peek(), Token::RParen) {
+			args.push(self.parse_expr()?);
+			match self.next() {
+				Token::Comma => {}
+				Token::RParen => return Some(args),
+				_ => return None,
+			}
+		}
+		self.next();
+		Some(args)
+	}
+
 	fn peek_op(&self) -> Option<Op> {
 		match self.peek() {
 			Token::Equals => Some(Op::Eq),
@@ -251,7 +917,89 @@ impl Parser {
 			Token::GreaterOrEqual => Some(Op::Ge),
 			Token::And => Some(Op::And),
 			Token::Or => Some(Op::Or),
+			Token::Plus => Some(Op::Add),
+			Token::Minus => Some(Op::Sub),
+			Token::Star => Some(Op::Mul),
+			Token::Slash => Some(Op::Div),
+			Token::Modulo => Some(Op::Mod),
+			Token::Begins => Some(Op::Begins),
+			Token::Matches => Some(Op::Matches),
+			Token::Contains => Some(Op::Contains),
 			_ => None,
 		}
 	}
 }
+
+const UNARY_PREC: u8 = 7;
+
+// Fields match by name alone here; which buffer they belong to is only
+// known at run time.
+fn accum_name(expr: &Expr) -> String {
+	match expr {
+		Expr::Identifier(name) => name.field.to_uppercase(),
+		other => format!("{:?}", other).to_uppercase(),
+	}
+}
+
+// Function bodies run while an expression is being evaluated, where no
+// record buffers can change, so they are limited to these statements.
+fn function_statement(stmt: &Statement) -> bool {
+	match stmt {
+		Statement::DefineVariable { .. } | Statement::Assign { .. } | Statement::Return { .. } => true,
+		Statement::If { then_branch, else_branch, .. } => {
+			function_statement(then_branch) && else_branch.as_deref().is_none_or(function_statement)
+		}
+		Statement::Do { iteration: None, body, .. } => body.iter().all(function_statement),
+		_ => false,
+	}
+}
+
+// The source text of a token, near enough for an error message.
+fn describe(token: &Token) -> String {
+	match token {
+		Token::Identifier(name) => name.clone(),
+		Token::StringLit(s) => format!("'{}'", s),
+		Token::Number(n) => n.to_string(),
+		Token::Decimal(d) => d.to_string(),
+		Token::Dot => ".".to_string(),
+		Token::Colon => ":".to_string(),
+		Token::Eof => "end of file".to_string(),
+		other => format!("{:?}", other).to_uppercase(),
+	}
+}
+
+fn precedence(op: &Op) -> u8 {
+	match op {
+		Op::Or => 1,
+		Op::And => 2,
+		Op::Eq | Op::Neq | Op::Lt | Op::Gt | Op::Le | Op::Ge => 4,
+		Op::Begins | Op::Matches | Op::Contains => 4,
+		Op::Add | Op::Sub => 5,
+		Op::Mul | Op::Div | Op::Mod => 6,
+	}
+}
+
+fn parse_data_type(name: &str) -> Option<DataType> {
+	match name.to_uppercase().as_str() {
+		"CHARACTER" | "CHAR" => Some(DataType::Character),
+		"INTEGER" | "INT" => Some(DataType::Integer),
+		"INT64" => Some(DataType::Int64),
+		"DECIMAL" | "DEC" => Some(DataType::Decimal),
+		"LOGICAL" => Some(DataType::Logical),
+		"DATE" => Some(DataType::Date),
+		"DATETIME" => Some(DataType::DateTime),
+		"DATETIME-TZ" => Some(DataType::DateTimeTz),
+		_ => None,
+	}
+}
+
+fn parse_aggregate(name: &str) -> Option<Aggregate> {
+	match name.to_uppercase().as_str() {
+		"TOTAL" => Some(Aggregate::Total),
+		"COUNT" => Some(Aggregate::Count),
+		"AVERAGE" => Some(Aggregate::Average),
+		"MAXIMUM" => Some(Aggregate::Maximum),
+		"MINIMUM" => Some(Aggregate::Minimum),
+		_ => None,
+	}
+}
@@ -1,83 +1,1881 @@
-use rusqlite::{Connection, Result};
+use rusqlite::functions::FunctionFlags;
+use rusqlite::types::Value as SqlValue;
+use rusqlite::{params_from_iter, Connection, Result};
+use std::cmp::Ordering;
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
 use std::error::Error;
+use std::fs;
+use std::path::PathBuf;
+use std::rc::Rc;
+use crate::accum::Accumulator;
+use crate::ast::{DataType, Expr, FieldRef, FindMode, Op, ParameterMode, SortKey, Statement, UnaryOp, UndoAction};
+use crate::builtins;
+use crate::date;
+use crate::error::AblError;
 use crate::lexer;
 use crate::parser::Parser;
-use crate::sqlgen::statement_to_sql;
+use crate::sqlgen::{self, Bindings, IndexHint, Seek, Select, Source, ROWID_COLUMN};
 use crate::tui::display_results;
+use crate::value::Value;
+
+type QueryResult = (Vec<String>, Vec<Vec<Value>>);
+
+#[derive(Clone)]
+struct TableInfo {
+    columns: Vec<String>,
+    // The ABL type each column's declared type implies, if any.
+    types: Vec<Option<DataType>>,
+    primary_key: Option<String>,
+    rowid_alias: Option<String>,
+}
+
+#[derive(Clone)]
+struct Record {
+    buffer: String,
+    rowid: i64,
+    columns: Vec<String>,
+    values: Vec<Value>,
+    dirty: Vec<bool>,
+    new: bool,
+}
+
+impl Record {
+    fn from_row(buffer: &str, cols: &[String], row: Vec<Value>) -> Self {
+        let mut rowid = 0;
+        let mut columns = Vec::new();
+        let mut values = Vec::new();
+        for (col, val) in cols.iter().zip(row) {
+            if col == ROWID_COLUMN {
+                if let Value::Integer(id) = val {
+                    rowid = id;
+                }
+            } else {
+                columns.push(col.clone());
+                values.push(val);
+            }
+        }
+        let dirty = vec![false; columns.len()];
+        Record { buffer: buffer.to_string(), rowid, columns, values, dirty, new: false }
+    }
+
+    // SQLite hands dates back as the ISO text they are stored as and logicals
+    // as 0 or 1; columns declared with those types turn them back.
+    fn convert(&mut self, info: &TableInfo) {
+        for (column, value) in self.columns.iter().zip(&mut self.values) {
+            let data_type = info.columns.iter().position(|c| c == column).and_then(|i| info.types[i]);
+            match (data_type, &value) {
+                (Some(DataType::Logical), Value::Integer(n)) => *value = Value::Logical(*n != 0),
+                (Some(data_type), Value::Character(_)) => {
+                    if let Ok(typed) = value.clone().coerce(data_type) {
+                        *value = typed;
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn create(buffer: &str, info: &TableInfo) -> Self {
+        Record {
+            buffer: buffer.to_string(),
+            rowid: 0,
+            columns: info.columns.clone(),
+            values: vec![Value::Unknown; info.columns.len()],
+            dirty: vec![false; info.columns.len()],
+            new: true,
+        }
+    }
+
+    fn dirty_fields(&self) -> (Vec<String>, Vec<Value>) {
+        self.columns
+            .iter()
+            .zip(&self.values)
+            .zip(&self.dirty)
+            .filter(|(_, dirty)| **dirty)
+            .map(|((c, v), _)| (c.clone(), v.clone()))
+            .unzip()
+    }
+
+    fn get(&self, field: &str) -> Option<&Value> {
+        self.columns
+            .iter()
+            .position(|c| c.eq_ignore_ascii_case(field))
+            .map(|i| &self.values[i])
+    }
+}
+
+#[derive(Clone)]
+struct Variable {
+    data_type: DataType,
+    decimals: Option<u32>,
+    value: Value,
+}
+
+impl Variable {
+    fn new(data_type: DataType, decimals: Option<u32>, value: Value) -> Result<Variable, String> {
+        let mut var = Variable { data_type, decimals, value: Value::Unknown };
+        var.set(value)?;
+        Ok(var)
+    }
+
+    // Coerces to the variable's type and rounds to its DECIMALS.
+    fn set(&mut self, value: Value) -> Result<(), String> {
+        self.value = match (value.coerce(self.data_type)?, self.decimals) {
+            (Value::Decimal(d), Some(places)) => Value::Decimal(d.round(places)),
+            (value, _) => value,
+        };
+        Ok(())
+    }
+}
+
+// A parsed .p file and the internal procedures and functions it defines.
+struct Program {
+    body: Rc<Vec<Statement>>,
+    procedures: HashMap<String, Rc<Vec<Statement>>>,
+    functions: HashMap<String, Rc<Function>>,
+}
+
+struct Function {
+    name: String,
+    returns: DataType,
+    params: Vec<(String, DataType)>,
+    body: Vec<Statement>,
+}
+
+impl Program {
+    fn new(body: Vec<Statement>) -> Self {
+        let mut procedures = HashMap::new();
+        let mut functions = HashMap::new();
+        for stmt in &body {
+            match stmt {
+                Statement::Procedure { name, body } => {
+                    procedures.insert(name.to_uppercase(), Rc::new(body.clone()));
+                }
+                Statement::Function { name, returns, params, body: Some(body) } => {
+                    let function = Function { name: name.clone(), returns: *returns, params: params.clone(), body: body.clone() };
+                    functions.insert(name.to_uppercase(), Rc::new(function));
+                }
+                _ => {}
+            }
+        }
+        Program { body: Rc::new(body), procedures, functions }
+    }
+}
+
+// One activation on the call stack; the bottom frame is the main procedure.
+// An internal procedure's variables shadow those of its program's main block,
+// which lives in the frame at `outer`.
+#[derive(Clone)]
+struct Call {
+    program: Rc<Program>,
+    variables: HashMap<String, Variable>,
+    outer: Option<usize>,
+}
+
+const MAX_CALL_DEPTH: usize = 200;
+
+enum Flow {
+    Normal,
+    Leave(Option<String>),
+    Next(Option<String>),
+    Undo { label: Option<String>, action: UndoAction },
+    Retry,
+    Return,
+}
+
+impl Flow {
+    // Resolves a signal raised in the body of an iterating block: Some(Normal)
+    // keeps iterating, None ends the loop and anything else propagates outward.
+    fn in_loop(self, label: Option<&str>) -> Option<Flow> {
+        match self {
+            Flow::Next(ref t) if targets(label, t) => Some(Flow::Normal),
+            Flow::Leave(ref t) if targets(label, t) => None,
+            other => Some(other),
+        }
+    }
+}
+
+// An unlabeled LEAVE, NEXT or UNDO addresses the innermost block that handles it.
+fn targets(label: Option<&str>, target: &Option<String>) -> bool {
+    match target {
+        None => true,
+        Some(t) => label.is_some_and(|l| l.eq_ignore_ascii_case(t)),
+    }
+}
+
+#[derive(Clone)]
+struct BreakGroup {
+    names: Vec<String>,
+    first: Vec<bool>,
+    last: Vec<bool>,
+}
+
+#[derive(Clone, Default)]
+struct ErrorStatus {
+    error: bool,
+    messages: Vec<String>,
+}
+
+struct Frame {
+    columns: Vec<String>,
+    places: Vec<Option<u32>>,
+    rows: Vec<Vec<Value>>,
+}
+
+// The state a query calling user functions hands SQLite to evaluate them in,
+// reached through HOSTS while the query runs.
+struct Host {
+    runtime: Runtime,
+    error: RefCell<Option<Box<dyn Error>>>,
+}
+
+thread_local! {
+    // Innermost last, as a function body can run a query of its own.
+    static HOSTS: RefCell<Vec<Rc<Host>>> = const { RefCell::new(Vec::new()) };
+}
 
 pub struct Runtime {
-    conn: Connection,
+    conn: Rc<Connection>,
+    verbose: bool,
+    buffers: Vec<Record>,
+    tables: HashMap<String, TableInfo>,
+    calls: Vec<Call>,
+    propath: Vec<PathBuf>,
+    programs: HashMap<PathBuf, Rc<Program>>,
+    // Parameters and variables of the user functions being evaluated.
+    locals: RefCell<Vec<HashMap<String, Variable>>>,
+    // User functions SQLite already knows by name.
+    registered: RefCell<HashSet<String>>,
+    break_groups: Vec<BreakGroup>,
+    accumulators: HashMap<(String, String), Accumulator>,
+    cursors: HashMap<String, i64>,
+    error_status: ErrorStatus,
+    transactions: usize,
+    caught: Vec<(String, AblError)>,
+    frame: Option<Frame>,
 }
 
 impl Runtime {
     pub fn new(db_path: &str) -> Result<Self, Box<dyn Error>> {
         let conn = Connection::open(db_path)?;
-        Ok(Runtime { conn })
+        builtins::register(&conn)?;
+        // SQLite knows the local time zone; the standard library does not.
+        let offset: f64 = conn.query_row("SELECT (julianday('now', 'localtime') - julianday('now')) * 1440", [], |row| row.get(0))?;
+        date::set_offset(offset.round() as i32);
+        Ok(Runtime {
+            conn: Rc::new(conn),
+            verbose: false,
+            buffers: Vec::new(),
+            tables: HashMap::new(),
+            calls: vec![Call {
+                program: Rc::new(Program::new(Vec::new())),
+                variables: HashMap::new(),
+                outer: None,
+            }],
+            propath: Vec::new(),
+            programs: HashMap::new(),
+            locals: RefCell::new(Vec::new()),
+            registered: RefCell::new(HashSet::new()),
+            break_groups: Vec::new(),
+            accumulators: HashMap::new(),
+            cursors: HashMap::new(),
+            error_status: ErrorStatus::default(),
+            transactions: 0,
+            caught: Vec::new(),
+            frame: None,
+        })
     }
-    
-    pub fn execute_query(&self, sql: &str) -> Result<(Vec<String>, Vec<Vec<String>>), Box<dyn Error>> {
-        let mut stmt = self.conn.prepare(sql)?;
+
+    pub fn execute_query(&self, sql: &str, params: &[Value]) -> Result<QueryResult, Box<dyn Error>> {
+        if self.verbose {
+            println!("[SQL] {}", sql);
+        }
+        let mut stmt = self.conn.prepare(sql).map_err(sql_error)?;
         let column_count = stmt.column_count();
         let column_names: Vec<String> = stmt.column_names().into_iter().map(|s| s.to_string()).collect();
-        
-        let rows = stmt.query_map([], |row| {
+
+        let rows = stmt.query_map(params_from_iter(params), |row| {
             let mut values = Vec::new();
             for i in 0..column_count {
-                let val_ref = row.get_ref(i)?;
-                let s = match val_ref {
-                    rusqlite::types::ValueRef::Null => "NULL".to_string(),
-                    rusqlite::types::ValueRef::Integer(i) => i.to_string(),
-                    rusqlite::types::ValueRef::Real(f) => f.to_string(),
-                    rusqlite::types::ValueRef::Text(t) => String::from_utf8_lossy(t).into_owned(),
-                    rusqlite::types::ValueRef::Blob(b) => format!("BLOB({}b)", b.len()),
-                };
-                values.push(s);
+                let value = Value::try_from(row.get::<_, SqlValue>(i)?);
+                values.push(value.map_err(|e| rusqlite::Error::UserFunctionError(e.into()))?);
             }
             Ok(values)
-        })?;
-        
+        }).map_err(sql_error)?;
+
         let mut results = Vec::new();
         for row in rows {
-            results.push(row?);
+            results.push(row.map_err(sql_error)?);
         }
-        
+
         Ok((column_names, results))
     }
-    
-    pub fn execute_update(&self, sql: &str) -> Result<usize, Box<dyn Error>> {
-        let affected = self.conn.execute(sql, [])?;
+
+    pub fn execute_update(&self, sql: &str, params: &[Value]) -> Result<usize, Box<dyn Error>> {
+        if self.verbose {
+            println!("[SQL] {}", sql);
+        }
+        let affected = self.conn.execute(sql, params_from_iter(params)).map_err(sql_error)?;
+        if self.verbose {
+            println!("[{} rows affected]", affected);
+        }
         Ok(affected)
     }
+
+    // As in ABL, the procedure block is only a transaction when it updates
+    // the database itself; otherwise each transaction-scoped block inside it
+    // commits on its own.
+    pub fn run(&mut self, statements: &[Statement]) -> Result<(), Box<dyn Error>> {
+        self.calls[0].program = Rc::new(Program::new(statements.to_vec()));
+        if updates_directly(statements, &variables(statements)) {
+            self.transaction(None, |rt| rt.exec_handled(statements, true, None))?;
+        } else {
+            self.exec_handled(statements, true, None)?;
+        }
+        Ok(())
+    }
+
+    // Runs one pass of a block that scopes a transaction: UNDO aimed at the
+    // block is resolved into the flow it asked for.
+    fn transaction<F>(&mut self, label: Option<&str>, f: F) -> Result<Flow, Box<dyn Error>>
+    where
+        F: FnOnce(&mut Self) -> Result<Flow, Box<dyn Error>>,
+    {
+        match self.atomic(f)? {
+            Flow::Undo { label: target, action } if targets(label, &target) => Ok(match action {
+                UndoAction::Leave(l) => Flow::Leave(l),
+                UndoAction::Next(l) => Flow::Next(l),
+                UndoAction::Retry => Flow::Retry,
+                UndoAction::Throw(_) => unreachable!("UNDO, THROW raises an error instead"),
+            }),
+            flow => Ok(flow),
+        }
+    }
+
+    // Runs `f` on a savepoint: its changes are kept when it completes and
+    // rolled back on an error or an UNDO.
+    fn atomic<F>(&mut self, f: F) -> Result<Flow, Box<dyn Error>>
+    where
+        F: FnOnce(&mut Self) -> Result<Flow, Box<dyn Error>>,
+    {
+        let depth = self.buffers.len();
+        self.transactions += 1;
+        self.savepoint("SAVEPOINT")?;
+        let result = f(self).and_then(|flow| match flow {
+            Flow::Undo { .. } => Ok(flow),
+            _ => self.write_pending().map(|_| flow),
+        });
+        match result {
+            Ok(Flow::Undo { label, action }) => {
+                self.rollback(depth)?;
+                Ok(Flow::Undo { label, action })
+            }
+            Ok(flow) => {
+                self.savepoint("RELEASE")?;
+                self.transactions -= 1;
+                Ok(flow)
+            }
+            Err(e) => {
+                self.rollback(depth)?;
+                Err(e)
+            }
+        }
+    }
+
+    fn savepoint(&self, command: &str) -> Result<(), Box<dyn Error>> {
+        let sql = format!("{} tx{}", command, self.transactions);
+        if self.verbose {
+            println!("[SQL] {}", sql);
+        }
+        self.conn.execute_batch(&sql)?;
+        Ok(())
+    }
+
+    // Undone records leave their buffers; the ones still in scope are read
+    // back so they match the database again.
+    fn rollback(&mut self, depth: usize) -> Result<(), Box<dyn Error>> {
+        self.savepoint("ROLLBACK TO")?;
+        self.savepoint("RELEASE")?;
+        self.transactions -= 1;
+        self.buffers.truncate(depth);
+        for record in std::mem::take(&mut self.buffers) {
+            if record.new {
+                self.buffers.push(record);
+                continue;
+            }
+            let (cols, mut rows) = self.execute_query(&sqlgen::select_rowid_sql(&record.buffer), &[Value::Integer(record.rowid)])?;
+            if !rows.is_empty() {
+                let record = self.read(&record.buffer, &cols, rows.remove(0));
+                self.buffers.push(record);
+            }
+        }
+        Ok(())
+    }
+
+    fn exec_block(&mut self, body: &[Statement]) -> Result<Flow, Box<dyn Error>> {
+        self.exec_handled(body, false, None)
+    }
+
+    // CATCH and FINALLY blocks trail the statements they guard. An error
+    // undoes those statements before the first CATCH of a matching class
+    // runs; FINALLY runs whatever happened. Errors no CATCH takes fall to
+    // the block's ON ERROR phrase, while errors raised by a CATCH or FINALLY
+    // go straight to the enclosing block.
+    fn exec_handled(&mut self, body: &[Statement], top_level: bool, on_error: Option<&UndoAction>) -> Result<Flow, Box<dyn Error>> {
+        let split = body
+            .iter()
+            .position(|s| matches!(s, Statement::Catch { .. } | Statement::Finally { .. }))
+            .unwrap_or(body.len());
+        let (main, handlers) = body.split_at(split);
+        let result = if handlers.is_empty() {
+            self.exec_statements(main, top_level)
+        } else {
+            self.atomic(|rt| rt.exec_statements(main, top_level))
+        };
+        let result = match result {
+            Err(e) => self.catch(handlers, on_error, e),
+            result => result,
+        };
+        for handler in handlers {
+            if let Statement::Finally { body } = handler {
+                let flow = self.exec_statements(body, top_level)?;
+                if !matches!(flow, Flow::Normal) {
+                    return Ok(flow);
+                }
+            }
+        }
+        result
+    }
+
+    fn catch(&mut self, handlers: &[Statement], on_error: Option<&UndoAction>, error: Box<dyn Error>) -> Result<Flow, Box<dyn Error>> {
+        let caught = AblError::from_error(error.as_ref());
+        for handler in handlers {
+            if let Statement::Catch { variable, class, body } = handler {
+                let class = class.rsplit('.').next().unwrap_or(class).to_uppercase();
+                let matches = match class.as_str() {
+                    "APPERROR" => caught.is_app(),
+                    "SYSERROR" => !caught.is_app(),
+                    _ => true,
+                };
+                if matches {
+                    self.caught.push((variable.to_uppercase(), caught));
+                    let result = self.exec_statements(body, false);
+                    self.caught.pop();
+                    return result;
+                }
+            }
+        }
+        match on_error {
+            Some(action) if !matches!(action, UndoAction::Throw(_)) => {
+                self.flush()?;
+                println!("{}", error);
+                Ok(Flow::Undo { label: None, action: action.clone() })
+            }
+            _ => Err(error),
+        }
+    }
+
+    fn exec_statements(&mut self, body: &[Statement], top_level: bool) -> Result<Flow, Box<dyn Error>> {
+        for stmt in body {
+            let flow = self.exec(stmt)?;
+            if top_level {
+                self.flush()?;
+            }
+            if !matches!(flow, Flow::Normal) {
+                return Ok(flow);
+            }
+        }
+        Ok(Flow::Normal)
+    }
+
+    fn exec(&mut self, stmt: &Statement) -> Result<Flow, Box<dyn Error>> {
+        match stmt {
+            Statement::ForEach { label, phrases, sort, breaks, body } => {
+                self.reset_accumulators(body);
+                let mut indexes = Vec::new();
+                for phrase in phrases {
+                    self.load_table(&phrase.table)?;
+                    indexes.push(match &phrase.use_index {
+                        Some(name) => self.index_columns(&phrase.table, name)?,
+                        None => None,
+                    });
+                }
+                let mut sources = Vec::new();
+                for (i, (phrase, index)) in phrases.iter().zip(&indexes).enumerate() {
+                    let mut condition = phrase.where_clause.clone();
+                    if let Some(parent) = &phrase.of {
+                        let joined = phrases[..i].iter().any(|p| p.table.eq_ignore_ascii_case(parent));
+                        if !joined && !self.buffers.iter().any(|r| r.buffer.eq_ignore_ascii_case(parent)) {
+                            return Err(format!("** No {} record is available. (91)", parent).into());
+                        }
+                        let join = self.join_condition(&phrase.table, parent)?;
+                        condition = Some(conjoin(join, condition));
+                    }
+                    sources.push(Source { table: &phrase.table, condition, index: index.as_ref(), outer_join: phrase.outer_join });
+                }
+                let (cols, rows) = self.select(Select { sources, sort, seek: None, limit: None })?;
+                let tables: Vec<&str> = phrases.iter().map(|p| p.table.as_str()).collect();
+                let mut saved = Vec::new();
+                for table in &tables {
+                    saved.extend(self.release(table)?);
+                }
+                let keys = if *breaks {
+                    self.break_keys(&tables, &cols, &rows, sort)?
+                } else {
+                    Vec::new()
+                };
+                if *breaks {
+                    let names = sort.iter().map(|k| group_name(&k.expr)).collect();
+                    self.break_groups.push(BreakGroup { names, first: Vec::new(), last: Vec::new() });
+                }
+                let result = self.iterate(&tables, &cols, rows, &keys, label.as_deref(), body);
+                if *breaks {
+                    self.break_groups.pop();
+                }
+                self.buffers.extend(saved);
+                result
+            }
+            Statement::Find { mode, table, key, where_clause, no_error } => {
+                let result = self.find(*mode, table, key.as_ref(), where_clause.as_ref());
+                self.no_error(*no_error, result)?;
+                Ok(Flow::Normal)
+            }
+            Statement::Create { table, no_error } => {
+                let result = self.create(table);
+                self.no_error(*no_error, result)?;
+                Ok(Flow::Normal)
+            }
+            Statement::Delete { table, no_error } => {
+                let result = self.delete(table);
+                self.no_error(*no_error, result)?;
+                Ok(Flow::Normal)
+            }
+            Statement::Assign { assignments, no_error } => {
+                let result = self.assign(assignments);
+                self.no_error(*no_error, result)?;
+                Ok(Flow::Normal)
+            }
+            Statement::DefineVariable { name, data_type, initial, decimals } => {
+                let key = name.to_uppercase();
+                if !self.scope().contains_key(&key) {
+                    let value = match initial {
+                        Some(expr) => self.eval(expr)?,
+                        None => Value::initial(*data_type),
+                    };
+                    self.scope().insert(key, Variable::new(*data_type, *decimals, value)?);
+                }
+                Ok(Flow::Normal)
+            }
+            // Parameters are bound by RUN before the body starts and
+            // procedures are collected before the script runs.
+            Statement::DefineParameter { .. } | Statement::Procedure { .. } | Statement::Function { .. } => Ok(Flow::Normal),
+            Statement::Run { name, args } => {
+                self.call(name, args)?;
+                Ok(Flow::Normal)
+            }
+            Statement::Display { items } => {
+                self.display(items)?;
+                Ok(Flow::Normal)
+            }
+            Statement::If { condition, then_branch, else_branch } => {
+                if self.eval(condition)?.is_true() {
+                    self.exec(then_branch)
+                } else if let Some(else_branch) = else_branch {
+                    self.exec(else_branch)
+                } else {
+                    Ok(Flow::Normal)
+                }
+            }
+            Statement::Do { label, iteration, condition, transaction, on_error, body } => {
+                let transaction = *transaction || on_error.is_some();
+                if iteration.is_none() && condition.is_none() && transaction {
+                    let mut retried = false;
+                    loop {
+                        match self.pass(true, label.as_deref(), on_error.as_ref(), body)?.in_loop(label.as_deref()) {
+                            Some(Flow::Retry) if !retried => retried = true,
+                            Some(Flow::Normal | Flow::Retry) | None => return Ok(Flow::Normal),
+                            Some(outer) => return Ok(outer),
+                        }
+                    }
+                }
+                if iteration.is_none() && condition.is_none() {
+                    let flow = self.exec_block(body)?;
+                    return Ok(match flow {
+                        Flow::Leave(Some(l)) | Flow::Next(Some(l)) if label.as_ref().is_some_and(|own| own.eq_ignore_ascii_case(&l)) => Flow::Normal,
+                        other => other,
+                    });
+                }
+                self.reset_accumulators(body);
+                let bounds = match iteration {
+                    Some(iter) => {
+                        let from = self.eval(&iter.from)?;
+                        let to = self.eval(&iter.to)?.coerce(DataType::Integer)?;
+                        let step = match &iter.by {
+                            Some(by) => self.eval(by)?.coerce(DataType::Integer)?,
+                            None => Value::Integer(1),
+                        };
+                        if step == Value::Integer(0) {
+                            return Err(format!("** DO {} loop cannot step BY 0.", iter.variable).into());
+                        }
+                        self.store(&iter.variable, from)?;
+                        Some((to, step))
+                    }
+                    None => None,
+                };
+                let mut retried = false;
+                loop {
+                    if let (Some(iter), Some((to, step))) = (iteration, &bounds) {
+                        let current = self.field_value(&iter.variable)?;
+                        let ordering = current.compare(to)?;
+                        let done = match step {
+                            Value::Integer(n) if *n < 0 => ordering == Some(Ordering::Less),
+                            _ => ordering == Some(Ordering::Greater),
+                        };
+                        if done {
+                            break;
+                        }
+                    }
+                    if let Some(cond) = condition {
+                        if !self.eval(cond)?.is_true() {
+                            break;
+                        }
+                    }
+                    match self.pass(transaction, label.as_deref(), on_error.as_ref(), body)?.in_loop(label.as_deref()) {
+                        Some(Flow::Retry) if !retried => {
+                            retried = true;
+                            continue;
+                        }
+                        Some(Flow::Normal | Flow::Retry) => {}
+                        Some(outer) => return Ok(outer),
+                        None => break,
+                    }
+                    retried = false;
+                    if let (Some(iter), Some((_, Value::Integer(step)))) = (iteration, &bounds) {
+                        // A step past the largest or smallest INTEGER is past `to` too.
+                        let next = match self.field_value(&iter.variable)? {
+                            Value::Integer(i) => match i.checked_add(*step) {
+                                Some(next) => Value::Integer(next),
+                                None => break,
+                            },
+                            other => other,
+                        };
+                        self.store(&iter.variable, next)?;
+                    }
+                }
+                Ok(Flow::Normal)
+            }
+            Statement::Repeat { label, condition, body } => {
+                self.reset_accumulators(body);
+                let transactional = updates(body);
+                let mut retried = false;
+                loop {
+                    if let Some(cond) = condition {
+                        if !self.eval(cond)?.is_true() {
+                            break;
+                        }
+                    }
+                    match self.pass(transactional, label.as_deref(), None, body)?.in_loop(label.as_deref()) {
+                        Some(Flow::Retry) if !retried => {
+                            retried = true;
+                            continue;
+                        }
+                        Some(Flow::Normal | Flow::Retry) => {}
+                        Some(outer) => return Ok(outer),
+                        None => break,
+                    }
+                    retried = false;
+                }
+                Ok(Flow::Normal)
+            }
+            Statement::Undo { action: UndoAction::Throw(variable), .. } => Err(Box::new(self.thrown(variable.as_deref())?)),
+            Statement::Undo { label, action } => Ok(Flow::Undo { label: label.clone(), action: action.clone() }),
+            Statement::Catch { .. } | Statement::Finally { .. } => Ok(Flow::Normal),
+            Statement::Return { error: true, value } => Err(self.return_error(value.as_ref())),
+            Statement::Return { .. } => Ok(Flow::Return),
+            Statement::Accumulate { items } => {
+                for item in items {
+                    let value = self.eval(&item.expr)?;
+                    let key = self.accum_key(&item.expr);
+                    let groups = std::iter::once(String::new()).chain(item.groups.iter().map(|g| group_key(g)));
+                    for group in groups {
+                        self.accumulators
+                            .entry((key.clone(), group))
+                            .or_insert_with(|| Accumulator::new(&item.aggregates))
+                            .add(&value)?;
+                    }
+                }
+                Ok(Flow::Normal)
+            }
+            Statement::Leave { label } => Ok(Flow::Leave(label.clone())),
+            Statement::Next { label } => Ok(Flow::Next(label.clone())),
+        }
+    }
+
+    // With NO-ERROR the failure is recorded in ERROR-STATUS instead of
+    // ending the procedure; every such statement starts from a clean status.
+    fn no_error(&mut self, no_error: bool, result: Result<(), Box<dyn Error>>) -> Result<(), Box<dyn Error>> {
+        if !no_error {
+            return result;
+        }
+        self.error_status = ErrorStatus::default();
+        if let Err(e) = result {
+            self.error_status.error = true;
+            self.error_status.messages.push(e.to_string());
+        }
+        Ok(())
+    }
+
+    fn find(&mut self, mode: FindMode, table: &str, key: Option<&Expr>, where_clause: Option<&Expr>) -> Result<(), Box<dyn Error>> {
+        self.load_table(table)?;
+        let mut condition = where_clause.cloned();
+        if let Some(key) = key {
+            let info = &self.tables[&table.to_uppercase()];
+            let column = info.primary_key.as_deref().unwrap_or("rowid");
+            let lookup = Expr::BinOp {
+                left: Box::new(Expr::Identifier(FieldRef::qualified(table, column))),
+                op: Op::Eq,
+                right: Box::new(key.clone()),
+            };
+            condition = Some(conjoin(lookup, condition));
+        }
+        let cursor = self.cursors.get(&table.to_uppercase()).copied();
+        let seek = match mode {
+            FindMode::Unique => None,
+            FindMode::First => Some(Seek::First),
+            FindMode::Last => Some(Seek::Last),
+            FindMode::Next => Some(cursor.map_or(Seek::First, Seek::After)),
+            FindMode::Prev => Some(cursor.map_or(Seek::Last, Seek::Before)),
+        };
+        let source = Source { table, condition, index: None, outer_join: false };
+        let (cols, mut rows) = self.select(Select {
+            sources: vec![source],
+            sort: &[],
+            seek,
+            limit: Some(if seek.is_none() { 2 } else { 1 }),
+        })?;
+        self.release(table)?;
+        if rows.len() > 1 {
+            return Err(format!("** More than one {} record found for a unique FIND. (3166)", table).into());
+        }
+        if rows.is_empty() {
+            // Falling off either end leaves the cursor there, so the
+            // opposite direction picks up the last or first record.
+            let edge = match mode {
+                FindMode::Next => Some(i64::MAX),
+                FindMode::Prev => Some(i64::MIN),
+                _ => None,
+            };
+            if let Some(edge) = edge {
+                self.cursors.insert(table.to_uppercase(), edge);
+            }
+            return Err(match mode {
+                FindMode::Unique => format!("** {} record not on file. (138)", table),
+                _ => format!("** FIND FIRST/LAST failed for table {}. (565)", table),
+            }
+            .into());
+        }
+        let record = self.read(table, &cols, rows.remove(0));
+        self.cursors.insert(table.to_uppercase(), record.rowid);
+        self.buffers.push(record);
+        Ok(())
+    }
+
+    fn create(&mut self, table: &str) -> Result<(), Box<dyn Error>> {
+        self.load_table(table)?;
+        self.release(table)?;
+        let record = Record::create(table, &self.tables[&table.to_uppercase()]);
+        self.buffers.push(record);
+        Ok(())
+    }
+
+    fn delete(&mut self, table: &str) -> Result<(), Box<dyn Error>> {
+        let record = self.take_buffer(table)
+            .ok_or_else(|| format!("** No {} record is available. (91)", table))?;
+        if !record.new {
+            self.execute_update(&sqlgen::delete_sql(table), &[Value::Integer(record.rowid)])?;
+        }
+        Ok(())
+    }
+
+    fn iterate(
+        &mut self,
+        tables: &[&str],
+        cols: &[String],
+        rows: Vec<Vec<Value>>,
+        keys: &[Vec<Value>],
+        label: Option<&str>,
+        body: &[Statement],
+    ) -> Result<Flow, Box<dyn Error>> {
+        let transactional = updates(body);
+        let mut retried = false;
+        let mut i = 0;
+        while i < rows.len() {
+            let iteration = |rt: &mut Self| {
+                let records = rt.split_row(tables, cols, rows[i].clone());
+                rt.buffers.extend(records);
+                if !keys.is_empty() {
+                    rt.mark_breaks(keys, i);
+                }
+                let result = rt.exec_block(body);
+                for table in tables.iter().rev() {
+                    rt.release(table)?;
+                }
+                result
+            };
+            let flow = if transactional { self.transaction(label, iteration)? } else { iteration(self)? };
+            match flow.in_loop(label) {
+                Some(Flow::Retry) if !retried => {
+                    retried = true;
+                    continue;
+                }
+                Some(Flow::Normal | Flow::Retry) => {}
+                Some(outer) => return Ok(outer),
+                None => break,
+            }
+            retried = false;
+            i += 1;
+        }
+        Ok(Flow::Normal)
+    }
+
+    fn pass(&mut self, transactional: bool, label: Option<&str>, on_error: Option<&UndoAction>, body: &[Statement]) -> Result<Flow, Box<dyn Error>> {
+        if transactional {
+            self.transaction(label, |rt| rt.exec_handled(body, false, on_error))
+        } else {
+            self.exec_handled(body, false, on_error)
+        }
+    }
+
+    // Binds the arguments to the procedure's parameters in the order they
+    // are defined, runs it as its own undoable block and copies OUTPUT and
+    // INPUT-OUTPUT parameters back to the caller's targets. A name ending in
+    // .p runs an external program found on the PROPATH.
+    fn call(&mut self, name: &str, args: &[(ParameterMode, Expr)]) -> Result<(), Box<dyn Error>> {
+        let top = self.calls.len() - 1;
+        let (program, outer) = if name.to_lowercase().ends_with(".p") {
+            (self.load(name)?, None)
+        } else {
+            let caller = &self.calls[top];
+            (caller.program.clone(), Some(caller.outer.unwrap_or(top)))
+        };
+        let body = match outer {
+            None => program.body.clone(),
+            Some(_) => program
+                .procedures
+                .get(&name.to_uppercase())
+                .cloned()
+                .ok_or_else(|| format!("** \"{}\" was not found. (293)", name))?,
+        };
+        let params: Vec<(ParameterMode, &str, DataType)> = body
+            .iter()
+            .filter_map(|s| match s {
+                Statement::DefineParameter { mode, name, data_type } => Some((*mode, name.as_str(), *data_type)),
+                _ => None,
+            })
+            .collect();
+        if params.len() != args.len() {
+            return Err(format!("** Mismatched number of parameters passed to routine {}. (3234)", name).into());
+        }
+        if self.calls.len() >= MAX_CALL_DEPTH {
+            return Err(format!("** Procedure call stack is too deep running {}.", name).into());
+        }
+
+        let mut variables = HashMap::new();
+        let mut outputs = Vec::new();
+        for ((mode, param, data_type), (arg_mode, expr)) in params.iter().zip(args) {
+            if mode != arg_mode {
+                return Err(format!("** Mismatched parameter types passed to routine {}. (3230)", name).into());
+            }
+            let value = match mode {
+                ParameterMode::Output => Value::initial(*data_type),
+                _ => self.eval(expr)?.coerce(*data_type)?,
+            };
+            if *mode != ParameterMode::Input {
+                match expr {
+                    Expr::Identifier(target) => outputs.push((param.to_uppercase(), target)),
+                    _ => return Err(format!("** OUTPUT parameter {} of {} must be a field or variable.", param, name).into()),
+                }
+            }
+            variables.insert(param.to_uppercase(), Variable::new(*data_type, None, value)?);
+        }
+
+        self.calls.push(Call { program, variables, outer });
+        self.reset_accumulators(&body);
+        let result = self.transaction(None, |rt| rt.exec_block(&body));
+        let call = self.calls.pop().expect("call pushed above");
+        result?;
+        for (param, target) in outputs {
+            self.store(target, call.variables[&param].value.clone())?;
+        }
+        Ok(())
+    }
+
+    // Finds an external program on the PROPATH, parsing each file only once.
+    fn load(&mut self, name: &str) -> Result<Rc<Program>, Box<dyn Error>> {
+        let path = self
+            .propath
+            .iter()
+            .map(|dir| dir.join(name))
+            .find(|path| path.is_file())
+            .ok_or_else(|| format!("** \"{}\" was not found. (293)", name))?;
+        if let Some(program) = self.programs.get(&path) {
+            return Ok(program.clone());
+        }
+        if self.verbose {
+            println!("[RUN] parsing {}", path.display());
+        }
+        let source = fs::read_to_string(&path)?;
+        let program = Rc::new(Program::new(parse(&source)?));
+        self.programs.insert(path, program.clone());
+        Ok(program)
+    }
+
+    fn function(&self, name: &str) -> Option<Rc<Function>> {
+        self.calls.last()?.program.functions.get(&name.to_uppercase()).cloned()
+    }
+
+    // User functions run from expressions, including WHERE clauses SQLite is
+    // evaluating, so they only get `&self`: their bodies are limited to
+    // statements that need no record buffers.
+    fn invoke(&self, name: &str, args: Vec<Value>) -> Result<Value, Box<dyn Error>> {
+        let function = self.function(name).ok_or_else(|| format!("** \"{}\" was not found. (293)", name))?;
+        if args.len() != function.params.len() {
+            return Err(format!("** Mismatched number of parameters passed to routine {}. (3234)", name).into());
+        }
+        if self.locals.borrow().len() >= MAX_CALL_DEPTH {
+            return Err(format!("** Procedure call stack is too deep running {}.", name).into());
+        }
+        let mut variables = HashMap::new();
+        for ((param, data_type), value) in function.params.iter().zip(args) {
+            variables.insert(param.to_uppercase(), Variable::new(*data_type, None, value)?);
+        }
+        self.locals.borrow_mut().push(variables);
+        let result = self.exec_function(&function.name, &function.body);
+        self.locals.borrow_mut().pop();
+        Ok(result?.unwrap_or(Value::Unknown).coerce(function.returns)?)
+    }
+
+    fn exec_function(&self, function: &str, body: &[Statement]) -> Result<Option<Value>, Box<dyn Error>> {
+        for stmt in body {
+            let returned = match stmt {
+                Statement::DefineVariable { name, data_type, initial, decimals } => {
+                    let value = match initial {
+                        Some(expr) => self.eval(expr)?,
+                        None => Value::initial(*data_type),
+                    };
+                    let var = Variable::new(*data_type, *decimals, value)?;
+                    let mut locals = self.locals.borrow_mut();
+                    let frame = locals.last_mut().expect("function frame");
+                    frame.entry(name.to_uppercase()).or_insert(var);
+                    None
+                }
+                Statement::Assign { assignments, .. } => {
+                    for (target, expr) in assignments {
+                        let value = self.eval(expr)?;
+                        let mut locals = self.locals.borrow_mut();
+                        let var = locals
+                            .last_mut()
+                            .filter(|_| target.table.is_none())
+                            .and_then(|frame| frame.get_mut(&target.field.to_uppercase()))
+                            .ok_or_else(|| format!("** FUNCTION {} can only assign its own parameters and variables, not {}.", function, target))?;
+                        var.set(value)?;
+                    }
+                    None
+                }
+                Statement::If { condition, then_branch, else_branch } => {
+                    if self.eval(condition)?.is_true() {
+                        self.exec_function(function, std::slice::from_ref(then_branch))?
+                    } else if let Some(else_branch) = else_branch {
+                        self.exec_function(function, std::slice::from_ref(else_branch))?
+                    } else {
+                        None
+                    }
+                }
+                Statement::Do { iteration: None, condition: None, body, .. } => self.exec_function(function, body)?,
+                Statement::Do { iteration: None, condition: Some(condition), body, .. } => {
+                    let mut returned = None;
+                    while returned.is_none() && self.eval(condition)?.is_true() {
+                        returned = self.exec_function(function, body)?;
+                    }
+                    returned
+                }
+                Statement::Return { error: false, value } => Some(match value {
+                    Some(expr) => self.eval(expr)?,
+                    None => Value::Unknown,
+                }),
+                Statement::Return { error: true, value } => return Err(self.return_error(value.as_ref())),
+                _ => unreachable!("the parser rejects other statements in FUNCTION {}", function),
+            };
+            if returned.is_some() {
+                return Ok(returned);
+            }
+        }
+        Ok(None)
+    }
+
+    fn return_error(&self, value: Option<&Expr>) -> Box<dyn Error> {
+        let value = match value.map(|expr| self.eval(expr)) {
+            Some(Ok(value)) => value.to_string(),
+            Some(Err(e)) => return e,
+            None => String::new(),
+        };
+        Box::new(AblError::app(value))
+    }
+
+    fn variable_value(&self, name: &str) -> Option<Value> {
+        let key = name.to_uppercase();
+        let local = self.locals.borrow().last().and_then(|frame| frame.get(&key).map(|v| v.value.clone()));
+        local.or_else(|| self.variable(name).map(|v| v.value.clone()))
+    }
+
+    fn variable_decimals(&self, name: &str) -> Option<u32> {
+        let key = name.to_uppercase();
+        let local = self.locals.borrow().last().and_then(|frame| frame.get(&key).map(|v| v.decimals));
+        local.unwrap_or_else(|| self.variable(name).and_then(|v| v.decimals))
+    }
+
+    fn variable(&self, name: &str) -> Option<&Variable> {
+        let key = name.to_uppercase();
+        let call = self.calls.last()?;
+        call.variables
+            .get(&key)
+            .or_else(|| call.outer.and_then(|i| self.calls[i].variables.get(&key)))
+    }
+
+    fn variable_mut(&mut self, name: &str) -> Option<&mut Variable> {
+        let key = name.to_uppercase();
+        let top = self.calls.len() - 1;
+        let index = match self.calls[top].variables.contains_key(&key) {
+            true => top,
+            false => self.calls[top].outer?,
+        };
+        self.calls[index].variables.get_mut(&key)
+    }
+
+    // Where DEFINE VARIABLE puts a new variable: the running procedure's frame.
+    fn scope(&mut self) -> &mut HashMap<String, Variable> {
+        &mut self.calls.last_mut().expect("main procedure frame").variables
+    }
+
+    fn thrown(&self, variable: Option<&str>) -> Result<AblError, Box<dyn Error>> {
+        let caught = match variable {
+            Some(name) => self.caught.iter().rev().find(|(v, _)| v.eq_ignore_ascii_case(name)),
+            None => self.caught.last(),
+        };
+        caught
+            .map(|(_, error)| error.clone())
+            .ok_or_else(|| format!("** UNDO, THROW has no error object{}.", variable.map(|v| format!(" named {}", v)).unwrap_or_default()).into())
+    }
+
+    fn break_keys(&mut self, tables: &[&str], cols: &[String], rows: &[Vec<Value>], sort: &[SortKey]) -> Result<Vec<Vec<Value>>, Box<dyn Error>> {
+        let mut keys = Vec::new();
+        for row in rows {
+            let records = self.split_row(tables, cols, row.clone());
+            self.buffers.extend(records);
+            let values = sort.iter().map(|k| self.eval(&k.expr)).collect::<Result<Vec<_>, _>>();
+            for table in tables.iter().rev() {
+                self.take_buffer(table);
+            }
+            keys.push(values?);
+        }
+        Ok(keys)
+    }
+
+    fn mark_breaks(&mut self, keys: &[Vec<Value>], i: usize) {
+        let levels = keys[i].len();
+        let first: Vec<bool> = (0..levels).map(|l| i == 0 || !same_group(&keys[i - 1][..=l], &keys[i][..=l])).collect();
+        let last: Vec<bool> = (0..levels).map(|l| i + 1 == keys.len() || !same_group(&keys[i + 1][..=l], &keys[i][..=l])).collect();
+        if let Some(group) = self.break_groups.last_mut() {
+            for (level, starts) in first.iter().enumerate() {
+                if *starts {
+                    let name = &group.names[level];
+                    self.accumulators.retain(|(_, g), _| g != name);
+                }
+            }
+            group.first = first;
+            group.last = last;
+        }
+    }
+
+    fn select(&mut self, select: Select) -> Result<QueryResult, Box<dyn Error>> {
+        for source in &select.sources {
+            self.load_table(source.table)?;
+        }
+        self.write_pending()?;
+        self.query(select)
+    }
+
+    fn query(&self, select: Select) -> Result<QueryResult, Box<dyn Error>> {
+        let query = select
+            .to_sql(self)
+            .ok_or_else(|| format!("Unable to translate WHERE clause for {}", select.sources[0].table))?;
+        let conditions = select.sources.iter().filter_map(|s| s.condition.as_ref());
+        if !conditions.chain(select.sort.iter().map(|k| &k.expr)).any(|e| self.calls_function(e)) {
+            return self.execute_query(&query.sql, &query.params);
+        }
+        self.register_functions()?;
+        let host = Rc::new(Host { runtime: self.snapshot(), error: RefCell::new(None) });
+        HOSTS.with(|hosts| hosts.borrow_mut().push(host.clone()));
+        let result = self.execute_query(&query.sql, &query.params);
+        HOSTS.with(|hosts| hosts.borrow_mut().pop());
+        result.map_err(|e| host.error.take().unwrap_or(e))
+    }
+
+    // SQLite calls a user function in a query back through the innermost
+    // host, so one registration serves every program defining that name.
+    fn register_functions(&self) -> Result<(), Box<dyn Error>> {
+        let program = &self.calls[self.calls.len() - 1].program;
+        for name in program.functions.keys() {
+            if !self.registered.borrow_mut().insert(name.clone()) {
+                continue;
+            }
+            let function = name.clone();
+            self.conn.create_scalar_function(&sqlgen::function_name(name), -1, FunctionFlags::SQLITE_UTF8, move |ctx| {
+                let args = (0..ctx.len())
+                    .map(|i| builtins::argument(ctx, i))
+                    .collect::<Result<Vec<_>>>()?;
+                let host = HOSTS
+                    .with(|hosts| hosts.borrow().last().cloned())
+                    .ok_or_else(|| rusqlite::Error::UserFunctionError(format!("** {} called outside a query.", function).into()))?;
+                // SQLite only passes the message on, so the error itself is
+                // kept for the query to report and a RETURN ERROR still
+                // reaches CATCH as an AppError.
+                host.runtime.invoke(&function, args).map_err(|e| {
+                    let message = e.to_string();
+                    host.error.replace(Some(e));
+                    rusqlite::Error::UserFunctionError(message.into())
+                })
+            })?;
+        }
+        Ok(())
+    }
+
+    // A copy of the state expressions read, for user functions SQLite calls
+    // during a query; their bodies cannot change it.
+    fn snapshot(&self) -> Runtime {
+        Runtime {
+            conn: self.conn.clone(),
+            verbose: self.verbose,
+            buffers: self.buffers.clone(),
+            tables: self.tables.clone(),
+            calls: self.calls.clone(),
+            propath: self.propath.clone(),
+            programs: self.programs.clone(),
+            locals: self.locals.clone(),
+            registered: self.registered.clone(),
+            break_groups: self.break_groups.clone(),
+            accumulators: self.accumulators.clone(),
+            cursors: self.cursors.clone(),
+            error_status: self.error_status.clone(),
+            transactions: self.transactions,
+            caught: self.caught.clone(),
+            frame: None,
+        }
+    }
+
+    // Whether the expression calls a user-defined function anywhere,
+    // including inside a nested CAN-FIND.
+    fn calls_function(&self, expr: &Expr) -> bool {
+        match expr {
+            Expr::Call { name, args } => !builtins::is_function(name) || args.iter().any(|a| self.calls_function(a)),
+            Expr::Group(inner) | Expr::Unary { expr: inner, .. } | Expr::Accum { expr: inner, .. } => self.calls_function(inner),
+            Expr::BinOp { left, right, .. } => self.calls_function(left) || self.calls_function(right),
+            Expr::CanFind { where_clause, .. } => where_clause.as_deref().is_some_and(|w| self.calls_function(w)),
+            Expr::Attribute { args, .. } => args.iter().any(|a| self.calls_function(a)),
+            _ => false,
+        }
+    }
+
+    fn index_columns(&self, table: &str, index: &str) -> Result<Option<IndexHint>, Box<dyn Error>> {
+        let mut stmt = self.conn.prepare(&format!("PRAGMA index_list(\"{}\")", table))?;
+        let names = stmt
+            .query_map([], |row| row.get::<_, String>(1))?
+            .collect::<Result<Vec<_>>>()?;
+        let Some(name) = names.into_iter().find(|n| n.eq_ignore_ascii_case(index)) else {
+            if self.verbose {
+                println!("[USE-INDEX {} ignored: no such index on {}]", index, table);
+            }
+            return Ok(None);
+        };
+        let mut stmt = self.conn.prepare(&format!("PRAGMA index_info(\"{}\")", name))?;
+        let columns = stmt
+            .query_map([], |row| row.get::<_, Option<String>>(2))?
+            .collect::<Result<Vec<_>>>()?;
+        Ok(Some(IndexHint { name, columns: columns.into_iter().flatten().collect() }))
+    }
+
+    // OF pairs the child with its parent through a declared foreign key
+    // (either direction) and falls back to the field names they share.
+    fn join_condition(&mut self, child: &str, parent: &str) -> Result<Expr, Box<dyn Error>> {
+        self.load_table(parent)?;
+        let mut pairs = self.foreign_key(child, parent)?;
+        if pairs.is_empty() {
+            pairs = self.foreign_key(parent, child)?.into_iter().map(|(p, c)| (c, p)).collect();
+        }
+        if pairs.is_empty() {
+            let parent_columns = &self.tables[&parent.to_uppercase()].columns;
+            pairs = self.tables[&child.to_uppercase()]
+                .columns
+                .iter()
+                .filter(|c| parent_columns.iter().any(|p| p.eq_ignore_ascii_case(c)))
+                .map(|c| (c.clone(), c.clone()))
+                .collect();
+        }
+        pairs
+            .into_iter()
+            .map(|(c, p)| Expr::BinOp {
+                left: Box::new(Expr::Identifier(FieldRef::qualified(child, &c))),
+                op: Op::Eq,
+                right: Box::new(Expr::Identifier(FieldRef::qualified(parent, &p))),
+            })
+            .reduce(|l, r| Expr::BinOp { left: Box::new(l), op: Op::And, right: Box::new(r) })
+            .ok_or_else(|| format!("** {} and {} have no relationship to join OF.", child, parent).into())
+    }
+
+    fn foreign_key(&self, table: &str, references: &str) -> Result<Vec<(String, String)>, Box<dyn Error>> {
+        let mut stmt = self.conn.prepare(&format!("PRAGMA foreign_key_list(\"{}\")", table))?;
+        let keys = stmt
+            .query_map([], |row| Ok((row.get::<_, i64>(0)?, row.get::<_, String>(2)?, row.get::<_, String>(3)?, row.get::<_, Option<String>>(4)?)))?
+            .collect::<Result<Vec<_>>>()?;
+        let Some(id) = keys.iter().find(|k| k.1.eq_ignore_ascii_case(references)).map(|k| k.0) else {
+            return Ok(Vec::new());
+        };
+        let primary_key = self.tables.get(&references.to_uppercase()).and_then(|t| t.rowid_alias.clone());
+        Ok(keys
+            .into_iter()
+            .filter(|k| k.0 == id)
+            .filter_map(|(_, _, from, to)| Some((from, to.or_else(|| primary_key.clone())?)))
+            .collect())
+    }
+
+    fn read(&self, buffer: &str, cols: &[String], row: Vec<Value>) -> Record {
+        let mut record = Record::from_row(buffer, cols, row);
+        if let Some(info) = self.tables.get(&buffer.to_uppercase()) {
+            record.convert(info);
+        }
+        record
+    }
+
+    // Splits a joined row into one record per buffer; a buffer whose rowid is
+    // NULL found no match in an OUTER-JOIN and stays unavailable.
+    fn split_row(&self, tables: &[&str], cols: &[String], row: Vec<Value>) -> Vec<Record> {
+        let starts: Vec<usize> = cols.iter().enumerate().filter(|(_, c)| *c == ROWID_COLUMN).map(|(i, _)| i).collect();
+        let mut records = Vec::new();
+        let mut row = row;
+        for (table, start) in tables.iter().zip(&starts).rev() {
+            let values = row.split_off(*start);
+            if values[0] != Value::Unknown {
+                records.push(self.read(table, &cols[*start..], values));
+            }
+        }
+        records.reverse();
+        records
+    }
+
+    fn load_table(&mut self, table: &str) -> Result<(), Box<dyn Error>> {
+        let key = table.to_uppercase();
+        if !self.tables.contains_key(&key) {
+            let info = self.table_info(table)?;
+            self.tables.insert(key, info);
+        }
+        Ok(())
+    }
+
+    fn table_info(&self, table: &str) -> Result<TableInfo, Box<dyn Error>> {
+        let mut stmt = self.conn.prepare(&format!("PRAGMA table_info(\"{}\")", table))?;
+        let columns = stmt
+            .query_map([], |row| Ok((row.get::<_, String>(1)?, row.get::<_, String>(2)?, row.get::<_, i64>(5)?)))?
+            .collect::<Result<Vec<_>>>()?;
+        if columns.is_empty() {
+            return Err(format!("** Unknown table {}.", table).into());
+        }
+        let keys: Vec<_> = columns.iter().filter(|(_, _, pk)| *pk > 0).collect();
+        let (primary_key, rowid_alias) = match keys.as_slice() {
+            [(name, ty, _)] => (Some(name.clone()), ty.eq_ignore_ascii_case("INTEGER").then(|| name.clone())),
+            _ => (None, None),
+        };
+        let types = columns.iter().map(|(_, ty, _)| column_type(ty)).collect();
+        let columns = columns.into_iter().map(|(name, _, _)| name).collect();
+        Ok(TableInfo { columns, types, primary_key, rowid_alias })
+    }
+
+    // Tables probed by CAN-FIND during evaluation may not be cached yet.
+    fn is_column(&self, table: &str, field: &str) -> bool {
+        let has = |t: &TableInfo| t.columns.iter().any(|c| c.eq_ignore_ascii_case(field));
+        match self.tables.get(&table.to_uppercase()) {
+            Some(info) => has(info),
+            None => self.table_info(table).is_ok_and(|info| has(&info)),
+        }
+    }
+
+    fn column_type(&self, table: &str, field: &str) -> Option<DataType> {
+        let find = |t: &TableInfo| t.columns.iter().position(|c| c.eq_ignore_ascii_case(field)).and_then(|i| t.types[i]);
+        match self.tables.get(&table.to_uppercase()) {
+            Some(info) => find(info),
+            None => self.table_info(table).ok().and_then(|info| find(&info)),
+        }
+    }
+
+    fn take_buffer(&mut self, buffer: &str) -> Option<Record> {
+        let pos = self.buffers.iter().rposition(|r| r.buffer.eq_ignore_ascii_case(buffer))?;
+        Some(self.buffers.remove(pos))
+    }
+
+    fn release(&mut self, buffer: &str) -> Result<Option<Record>, Box<dyn Error>> {
+        match self.take_buffer(buffer) {
+            Some(mut record) => {
+                if record.new {
+                    self.write_record(&mut record)?;
+                }
+                Ok(Some(record))
+            }
+            None => Ok(None),
+        }
+    }
+
+    fn write_pending(&mut self) -> Result<(), Box<dyn Error>> {
+        let mut buffers = std::mem::take(&mut self.buffers);
+        let result = buffers
+            .iter_mut()
+            .filter(|r| r.new)
+            .try_for_each(|r| self.write_record(r));
+        self.buffers = buffers;
+        result
+    }
+
+    fn write_record(&self, record: &mut Record) -> Result<(), Box<dyn Error>> {
+        let (columns, mut values) = record.dirty_fields();
+        if record.new {
+            self.execute_update(&sqlgen::insert_sql(&record.buffer, &columns), &values)?;
+            record.rowid = self.conn.last_insert_rowid();
+            let (cols, mut rows) = self.execute_query(&sqlgen::select_rowid_sql(&record.buffer), &[Value::Integer(record.rowid)])?;
+            if !rows.is_empty() {
+                *record = self.read(&record.buffer, &cols, rows.remove(0));
+            }
+            return Ok(());
+        }
+        if columns.is_empty() {
+            return Ok(());
+        }
+        values.push(Value::Integer(record.rowid));
+        self.execute_update(&sqlgen::update_sql(&record.buffer, &columns), &values)?;
+        if let Some(alias) = &self.tables[&record.buffer.to_uppercase()].rowid_alias {
+            if let Some(Value::Integer(id)) = record.get(alias) {
+                record.rowid = *id;
+            }
+        }
+        record.dirty.iter_mut().for_each(|d| *d = false);
+        Ok(())
+    }
+
+    fn assign(&mut self, assignments: &[(FieldRef, Expr)]) -> Result<(), Box<dyn Error>> {
+        let mut touched = Vec::new();
+        for (target, expr) in assignments {
+            let value = self.eval(expr)?;
+            if let Some(index) = self.set_value(target, value)? {
+                if !touched.contains(&index) {
+                    touched.push(index);
+                }
+            }
+        }
+        let mut buffers = std::mem::take(&mut self.buffers);
+        let result = touched.into_iter().try_for_each(|i| self.write_record(&mut buffers[i]));
+        self.buffers = buffers;
+        result
+    }
+
+    fn store(&mut self, target: &FieldRef, value: Value) -> Result<(), Box<dyn Error>> {
+        if let Some(index) = self.set_value(target, value)? {
+            let mut record = self.buffers.remove(index);
+            let result = self.write_record(&mut record);
+            self.buffers.insert(index, record);
+            result?;
+        }
+        Ok(())
+    }
+
+    fn set_value(&mut self, target: &FieldRef, value: Value) -> Result<Option<usize>, Box<dyn Error>> {
+        if target.table.is_none() {
+            if let Some(var) = self.variable_mut(&target.field) {
+                var.set(value)?;
+                return Ok(None);
+            }
+        }
+        let (index, field) = self.field_slot(target)?;
+        let record = &mut self.buffers[index];
+        record.values[field] = value;
+        record.dirty[field] = true;
+        Ok(Some(index))
+    }
+
+    fn field_slot(&self, name: &FieldRef) -> Result<(usize, usize), Box<dyn Error>> {
+        let unknown = || format!("** Unknown Field or Variable name - {}. (201)", name);
+        let slot = |r: &Record| r.columns.iter().position(|c| c.eq_ignore_ascii_case(&name.field));
+        if let Some(buffer) = &name.table {
+            let index = self.buffer_index(buffer)?;
+            return Ok((index, slot(&self.buffers[index]).ok_or_else(unknown)?));
+        }
+        self.buffers
+            .iter()
+            .enumerate()
+            .rev()
+            .find_map(|(i, r)| slot(r).map(|f| (i, f)))
+            .ok_or_else(|| unknown().into())
+    }
+
+    fn field_value(&self, name: &FieldRef) -> Result<Value, Box<dyn Error>> {
+        let unknown = || format!("** Unknown Field or Variable name - {}. (201)", name);
+        if let Some(buffer) = &name.table {
+            let record = &self.buffers[self.buffer_index(buffer)?];
+            return record.get(&name.field).cloned().ok_or_else(|| unknown().into());
+        }
+        if let Some(value) = self.variable_value(&name.field) {
+            return Ok(value);
+        }
+        self.buffers
+            .iter()
+            .rev()
+            .find_map(|r| r.get(&name.field).cloned())
+            .ok_or_else(|| unknown().into())
+    }
+
+    // Fields are accumulated under their buffer and name however they are
+    // written, so Amount and Order.Amount share one accumulator.
+    fn accum_key(&self, expr: &Expr) -> String {
+        let Expr::Identifier(name) = expr else { return expr_key(expr) };
+        let field = name.field.to_uppercase();
+        if let Some(table) = &name.table {
+            return format!("{}.{}", table.to_uppercase(), field);
+        }
+        if self.variable_value(&name.field).is_some() {
+            return field;
+        }
+        match self.buffers.iter().rev().find(|r| r.get(&name.field).is_some()) {
+            Some(record) => format!("{}.{}", record.buffer.to_uppercase(), field),
+            None => field,
+        }
+    }
+
+    // Loops and procedures start the totals of their own ACCUMULATE
+    // statements afresh each time they are entered; plain DO blocks belong
+    // to the block around them.
+    fn reset_accumulators(&mut self, body: &[Statement]) {
+        for stmt in body {
+            match stmt {
+                Statement::Accumulate { items } => {
+                    for item in items {
+                        self.accumulators.retain(|(key, _), _| !accumulates(key, &item.expr));
+                    }
+                }
+                Statement::If { then_branch, else_branch, .. } => {
+                    self.reset_accumulators(std::slice::from_ref(then_branch));
+                    if let Some(else_branch) = else_branch {
+                        self.reset_accumulators(std::slice::from_ref(else_branch));
+                    }
+                }
+                Statement::Do { iteration: None, condition: None, body, .. } => self.reset_accumulators(body),
+                _ => {}
+            }
+        }
+    }
+
+    fn buffer_index(&self, buffer: &str) -> Result<usize, Box<dyn Error>> {
+        self.buffers
+            .iter()
+            .rposition(|r| r.buffer.eq_ignore_ascii_case(buffer))
+            .ok_or_else(|| format!("** No {} record is available. (91)", buffer).into())
+    }
+
+    fn eval(&self, expr: &Expr) -> Result<Value, Box<dyn Error>> {
+        match expr {
+            Expr::Identifier(name) => self.field_value(name),
+            Expr::String(s) => Ok(Value::Character(s.clone())),
+            Expr::Number(n) => Ok(Value::Integer(*n)),
+            Expr::Decimal(d) => Ok(Value::Decimal(*d)),
+            Expr::Date(parts) => Ok(Value::Date(date::from_parts(*parts)?)),
+            Expr::Logical(b) => Ok(Value::Logical(*b)),
+            Expr::Unknown => Ok(Value::Unknown),
+            Expr::Group(inner) => self.eval(inner),
+            Expr::BinOp { left, op, right } => {
+                let l = self.eval(left)?;
+                match op {
+                    // Three-valued logic: an unknown operand only decides the
+                    // result when the other one does not.
+                    Op::And if l == Value::Logical(false) => Ok(Value::Logical(false)),
+                    Op::Or if l.is_true() => Ok(Value::Logical(true)),
+                    Op::And => Ok(match (l, self.eval(right)?) {
+                        (_, Value::Logical(false)) => Value::Logical(false),
+                        (Value::Logical(true), Value::Logical(true)) => Value::Logical(true),
+                        _ => Value::Unknown,
+                    }),
+                    Op::Or => Ok(match (l, self.eval(right)?) {
+                        (_, Value::Logical(true)) => Value::Logical(true),
+                        (Value::Logical(false), Value::Logical(false)) => Value::Logical(false),
+                        _ => Value::Unknown,
+                    }),
+                    Op::Add => Ok(l.add(&self.eval(right)?)?),
+                    Op::Sub => Ok(l.sub(&self.eval(right)?)?),
+                    Op::Mul => Ok(l.mul(&self.eval(right)?)?),
+                    Op::Div => Ok(l.div(&self.eval(right)?)?),
+                    Op::Mod => Ok(l.modulo(&self.eval(right)?)?),
+                    Op::Begins | Op::Matches | Op::Contains => match (l, self.eval(right)?) {
+                        (Value::Character(a), Value::Character(b)) => Ok(Value::Logical(match op {
+                            Op::Begins => builtins::begins(&a, &b),
+                            Op::Matches => builtins::matches(&a, &b),
+                            _ => builtins::contains(&a, &b),
+                        })),
+                        (Value::Unknown, _) | (_, Value::Unknown) => Ok(Value::Unknown),
+                        _ => Err("** Incompatible data types in expression or assignment. (223)".into()),
+                    },
+                    _ => compare(op, &l, &self.eval(right)?),
+                }
+            }
+            Expr::FirstOf(name) | Expr::LastOf(name) => {
+                let wanted = group_key(name);
+                for group in self.break_groups.iter().rev() {
+                    if let Some(level) = group.names.iter().position(|n| *n == wanted) {
+                        let flags = if matches!(expr, Expr::LastOf(_)) { &group.last } else { &group.first };
+                        return Ok(Value::Logical(flags.get(level).copied().unwrap_or(false)));
+                    }
+                }
+                Err(format!("** {} is not a BREAK BY group of an enclosing FOR EACH.", name).into())
+            }
+            Expr::Accum { aggregate, group, expr } => {
+                let group = group.as_deref().map(group_key).unwrap_or_default();
+                let key = (self.accum_key(expr), group);
+                // After the loop its buffer is gone, so a bare field name
+                // matches whichever table it was accumulated for.
+                let accumulator = self.accumulators.get(&key).or_else(|| match expr.as_ref() {
+                    Expr::Identifier(name) if name.table.is_none() => {
+                        let suffix = format!(".{}", name.field.to_uppercase());
+                        self.accumulators.iter().find(|((k, g), _)| *g == key.1 && k.ends_with(&suffix)).map(|(_, a)| a)
+                    }
+                    _ => None,
+                });
+                match accumulator {
+                    Some(acc) => Ok(acc.result(*aggregate)?),
+                    None => Ok(Accumulator::empty(*aggregate)),
+                }
+            }
+            Expr::CanFind { mode, table, where_clause } => {
+                let unique = *mode == FindMode::Unique;
+                let source = Source { table, condition: where_clause.as_deref().cloned(), index: None, outer_join: false };
+                let select = Select {
+                    sources: vec![source],
+                    sort: &[],
+                    seek: None,
+                    limit: Some(if unique { 2 } else { 1 }),
+                };
+                let (_, rows) = self.query(select)?;
+                Ok(Value::Logical(if unique { rows.len() == 1 } else { !rows.is_empty() }))
+            }
+            Expr::Available(buffer) => Ok(Value::Logical(self.buffers.iter().any(|r| r.buffer.eq_ignore_ascii_case(buffer)))),
+            Expr::Attribute { handle, name, args } => self.attribute(handle, name, args),
+            Expr::Call { name, args } => {
+                let args = args.iter().map(|a| self.eval(a)).collect::<Result<Vec<_>, _>>()?;
+                if builtins::is_function(name) {
+                    return Ok(builtins::call(name, &args)?);
+                }
+                self.invoke(name, args)
+            }
+            Expr::Unary { op, expr } => {
+                let value = self.eval(expr)?;
+                match op {
+                    UnaryOp::Neg => Ok(value.negate()?),
+                    UnaryOp::Not => match value {
+                        Value::Logical(b) => Ok(Value::Logical(!b)),
+                        Value::Unknown => Ok(Value::Unknown),
+                        _ => Err("** Incompatible data types in expression or assignment. (223)".into()),
+                    },
+                }
+            }
+        }
+    }
+
+    fn attribute(&self, handle: &str, name: &str, args: &[Expr]) -> Result<Value, Box<dyn Error>> {
+        if let Some((_, error)) = self.caught.iter().rev().find(|(v, _)| v.eq_ignore_ascii_case(handle)) {
+            return self.error_member(error, handle, name, args);
+        }
+        if !handle.eq_ignore_ascii_case("ERROR-STATUS") {
+            return Err(format!("** Unknown Field or Variable name - {}. (201)", handle).into());
+        }
+        match name.to_uppercase().as_str() {
+            "ERROR" => Ok(Value::Logical(self.error_status.error)),
+            "NUM-MESSAGES" => Ok(Value::Integer(self.error_status.messages.len() as i64)),
+            "GET-MESSAGE" => {
+                let index = match args.first() {
+                    Some(arg) => self.eval(arg)?.coerce(DataType::Integer)?,
+                    None => Value::Integer(1),
+                };
+                let message = match index {
+                    Value::Integer(n) if n >= 1 => self.error_status.messages.get(n as usize - 1).cloned(),
+                    _ => None,
+                };
+                Ok(Value::Character(message.unwrap_or_default()))
+            }
+            _ => Err(format!("** {} is not a queryable attribute for {}. (4052)", name, handle).into()),
+        }
+    }
+
+    fn error_member(&self, error: &AblError, handle: &str, name: &str, args: &[Expr]) -> Result<Value, Box<dyn Error>> {
+        let index = match args.first() {
+            Some(arg) => match self.eval(arg)?.coerce(DataType::Integer)? {
+                Value::Integer(n) => n,
+                _ => 0,
+            },
+            None => 1,
+        };
+        match name.to_uppercase().as_str() {
+            "NUMMESSAGES" => Ok(Value::Integer(error.messages.len() as i64)),
+            "GETMESSAGE" => Ok(Value::Character(error.message(index))),
+            "GETMESSAGENUM" => Ok(Value::Integer(error.message_number(index))),
+            "RETURNVALUE" => Ok(Value::Character(error.return_value.clone().unwrap_or_default())),
+            _ => Err(format!("** {} is not a queryable attribute for {}. (4052)", name, handle).into()),
+        }
+    }
+
+    fn display(&mut self, items: &[Expr]) -> Result<(), Box<dyn Error>> {
+        let literals_only = items.iter().all(|i| matches!(i, Expr::String(_)));
+        if literals_only {
+            let msg = items
+                .iter()
+                .filter_map(|i| match i {
+                    Expr::String(s) => Some(s.as_str()),
+                    _ => None,
+                })
+                .collect::<Vec<_>>()
+                .join(" ");
+            self.flush()?;
+            println!("{}", msg);
+            return Ok(());
+        }
+
+        let mut columns = Vec::new();
+        let mut places = Vec::new();
+        let mut row = Vec::new();
+        for item in items {
+            let label = match item {
+                Expr::String(_) | Expr::Number(_) | Expr::Decimal(_) => continue,
+                Expr::Identifier(name) => name.field.as_str(),
+                _ => "",
+            };
+            columns.push(label.to_string());
+            places.push(match item {
+                Expr::Identifier(name) if name.table.is_none() => self.variable_decimals(&name.field),
+                _ => None,
+            });
+            row.push(self.eval(item)?);
+        }
+        if self.frame.as_ref().is_some_and(|f| f.columns != columns || f.places != places) {
+            self.flush()?;
+        }
+        self.frame
+            .get_or_insert_with(|| Frame { columns, places, rows: Vec::new() })
+            .rows
+            .push(row);
+        Ok(())
+    }
+
+    fn flush(&mut self) -> Result<(), Box<dyn Error>> {
+        if let Some(frame) = self.frame.take() {
+            display_results(&frame.columns, &frame.places, &frame.rows)?;
+        }
+        Ok(())
+    }
 }
 
-pub fn execute(db_path: &str, source: &str, verbose: bool) -> Result<(), Box<dyn Error>> {
-    let runtime = Runtime::new(db_path)?;
+impl Bindings for Runtime {
+    fn is_column(&self, table: &str, field: &str) -> bool {
+        Runtime::is_column(self, table, field)
+    }
+
+    fn column_type(&self, table: &str, field: &str) -> Option<DataType> {
+        Runtime::column_type(self, table, field)
+    }
+
+    fn variable(&self, name: &str) -> Option<Value> {
+        self.variable_value(name)
+    }
+
+    fn field(&self, name: &FieldRef) -> Option<Value> {
+        match name.table {
+            Some(_) => self.field_value(name).ok(),
+            None => self.buffers.iter().rev().find_map(|r| r.get(&name.field).cloned()),
+        }
+    }
+
+    fn evaluate(&self, expr: &Expr) -> Option<Value> {
+        self.eval(expr).ok()
+    }
+
+    fn returns(&self, function: &str) -> Option<DataType> {
+        if builtins::is_function(function) {
+            return builtins::returns(function);
+        }
+        self.function(function).map(|f| f.returns)
+    }
+}
+
+fn column_type(declared: &str) -> Option<DataType> {
+    match declared.to_uppercase().replace(['-', '_'], "").as_str() {
+        "DATE" => Some(DataType::Date),
+        "DATETIME" => Some(DataType::DateTime),
+        "DATETIMETZ" => Some(DataType::DateTimeTz),
+        "LOGICAL" | "BOOLEAN" => Some(DataType::Logical),
+        // SQLite's rule for text affinity.
+        ty if ty.contains("CHAR") || ty.contains("CLOB") || ty.contains("TEXT") => Some(DataType::Character),
+        _ => None,
+    }
+}
+
+// Blocks whose body can change the database run each pass as a
+// sub-transaction so that UNDO and errors only roll back that pass.
+fn updates(body: &[Statement]) -> bool {
+    body.iter().any(|stmt| match stmt {
+        Statement::Create { .. }
+        | Statement::Delete { .. }
+        | Statement::Assign { .. }
+        | Statement::Undo { .. }
+        | Statement::Run { .. } => true,
+        Statement::ForEach { body, .. }
+        | Statement::Do { body, .. }
+        | Statement::Repeat { body, .. }
+        | Statement::Catch { body, .. }
+        | Statement::Finally { body } => updates(body),
+        Statement::If { then_branch, else_branch, .. } => {
+            updates(std::slice::from_ref(then_branch)) || else_branch.as_deref().is_some_and(|e| updates(std::slice::from_ref(e)))
+        }
+        _ => false,
+    })
+}
+
+// Updates made by the block itself rather than by a FOR EACH, REPEAT, DO
+// TRANSACTION or procedure it runs, which scope their own transactions.
+// Assignments only count when they target a field.
+fn updates_directly(body: &[Statement], variables: &HashSet<String>) -> bool {
+    body.iter().any(|stmt| match stmt {
+        Statement::Create { .. } | Statement::Delete { .. } | Statement::Undo { .. } => true,
+        Statement::Assign { assignments, .. } => assignments
+            .iter()
+            .any(|(target, _)| target.table.is_some() || !variables.contains(&target.field.to_uppercase())),
+        Statement::Do { transaction: false, on_error: None, body, .. } | Statement::Catch { body, .. } | Statement::Finally { body } => {
+            updates_directly(body, variables)
+        }
+        Statement::If { then_branch, else_branch, .. } => {
+            updates_directly(std::slice::from_ref(then_branch), variables)
+                || else_branch.as_deref().is_some_and(|e| updates_directly(std::slice::from_ref(e), variables))
+        }
+        _ => false,
+    })
+}
+
+fn variables(body: &[Statement]) -> HashSet<String> {
+    let mut names = HashSet::new();
+    for stmt in body {
+        match stmt {
+            Statement::DefineVariable { name, .. } => {
+                names.insert(name.to_uppercase());
+            }
+            Statement::Do { body, .. } | Statement::ForEach { body, .. } | Statement::Repeat { body, .. } => names.extend(variables(body)),
+            Statement::If { then_branch, else_branch, .. } => {
+                names.extend(variables(std::slice::from_ref(then_branch)));
+                if let Some(e) = else_branch {
+                    names.extend(variables(std::slice::from_ref(e)));
+                }
+            }
+            _ => {}
+        }
+    }
+    names
+}
+
+fn sql_error(error: rusqlite::Error) -> Box<dyn Error> {
+    match error {
+        // A function's own error, already in ABL form.
+        rusqlite::Error::SqliteFailure(_, Some(message)) if message.starts_with("** ") => message.into(),
+        rusqlite::Error::UserFunctionError(error) => error,
+        rusqlite::Error::SqliteFailure(failure, message) => {
+            let message = message.unwrap_or_else(|| failure.to_string());
+            format!("** {}. ({})", message, failure.extended_code).into()
+        }
+        other => format!("** {}", other).into(),
+    }
+}
+
+// Break keys compare the way ABL compares values, so "acme" and "ACME"
+// fall in one group.
+fn same_group(a: &[Value], b: &[Value]) -> bool {
+    a.iter().zip(b).all(|(a, b)| a == b || a.compare(b) == Ok(Some(Ordering::Equal)))
+}
+
+fn conjoin(condition: Expr, rest: Option<Expr>) -> Expr {
+    match rest {
+        Some(rest) => Expr::BinOp { left: Box::new(condition), op: Op::And, right: Box::new(Expr::Group(Box::new(rest))) },
+        None => condition,
+    }
+}
+
+fn group_key(name: &str) -> String {
+    name.rsplit('.').next().unwrap_or(name).to_uppercase()
+}
+
+fn group_name(expr: &Expr) -> String {
+    match expr {
+        Expr::Identifier(name) => name.field.to_uppercase(),
+        _ => expr_key(expr),
+    }
+}
+
+// Whether an accumulator key, as built by accum_key, is for `expr`.
+fn accumulates(key: &str, expr: &Expr) -> bool {
+    match expr {
+        Expr::Identifier(name) => {
+            let field = name.field.to_uppercase();
+            key == field || key.ends_with(&format!(".{}", field))
+        }
+        other => key == expr_key(other),
+    }
+}
+
+fn expr_key(expr: &Expr) -> String {
+    format!("{:?}", expr).to_uppercase()
+}
+
+fn compare(op: &Op, l: &Value, r: &Value) -> Result<Value, Box<dyn Error>> {
+    let ordering = l.compare(r)?;
+    let unknown = ordering.is_none();
+    let result = match op {
+        Op::Eq => ordering == Some(Ordering::Equal) || (unknown && l == r),
+        Op::Neq => !(ordering == Some(Ordering::Equal) || (unknown && l == r)),
+        _ if unknown => return Ok(Value::Unknown),
+        Op::Lt => ordering == Some(Ordering::Less),
+        Op::Gt => ordering == Some(Ordering::Greater),
+        Op::Le => ordering != Some(Ordering::Greater),
+        Op::Ge => ordering != Some(Ordering::Less),
+        _ => unreachable!("arithmetic and logical operators are evaluated by Runtime::eval"),
+    };
+    Ok(Value::Logical(result))
+}
+
+fn parse(source: &str) -> Result<Vec<Statement>, String> {
+    Parser::new(lexer::lex(source)).parse_statements()
+}
+
+pub fn execute(db_path: &str, source: &str, propath: &[PathBuf], verbose: bool) -> Result<(), Box<dyn Error>> {
+    let mut runtime = Runtime::new(db_path)?;
+    runtime.verbose = verbose;
+    runtime.propath = propath.to_vec();
 
     let tokens = lexer::lex(source);
     if verbose {
         println!("Tokens: {:?}", tokens);
     }
     let mut parser = Parser::new(tokens);
-    let statements = parser.parse_statements();
+    let statements = parser.parse_statements()?;
     if verbose {
         println!("Statements: {} found", statements.len());
     }
 
-    for stmt in &statements {
-        if let Some(sql) = statement_to_sql(stmt) {
-            if verbose {
-                println!("[SQL] {}", sql);
-            }
-            if sql.trim_start().to_uppercase().starts_with("SELECT") {
-                let (cols, results) = runtime.execute_query(&sql)?;
-                display_results(&cols, &results)?;
-            } else {
-                let affected = runtime.execute_update(&sql)?;
-                if verbose {
-                    println!("[{} rows affected]", affected);
-                }
-            }
-        }
-    }
-    Ok(())
-}
\ No newline at end of file
+    runtime.run(&statements)
+}
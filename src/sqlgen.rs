@@ -1,87 +1,348 @@
-use crate::ast::{Statement, Expr, Op};
-
-pub fn statement_to_sql(stmt: &Statement) -> Option<String> {
-	match stmt {
-		Statement::ForEach { table, where_clause, body } => {
-			for s in body {
-				if let Statement::Display { fields } = s {
-					let literals_only = !fields.is_empty() && fields.iter().all(|f| f.starts_with('"') && f.ends_with('"'));
-					if literals_only {
-						let msg = fields.iter().map(|f| f.trim_matches('"')).collect::<Vec<_>>().join(" ");
-						println!("{}", msg);
-						return None;
-					} else {
-						let to_select: Vec<String> = fields.iter().filter(|f| !f.starts_with('"') && f.parse::<f64>().is_err()).cloned().collect();
-						let fields_sql = if to_select.is_empty() { "*".to_string() } else { to_select.iter().map(|f| format!("\"{}\"", f)).collect::<Vec<_>>().join(", ") };
-						let mut sql = format!("SELECT {} FROM \"{}\"", fields_sql, table);
-						if let Some(expr) = where_clause {
-							if let Some(w) = expr_to_sql(expr) {
-								sql.push_str(&format!(" WHERE {}", w));
-							}
-						}
-						return Some(sql);
+use crate::ast::{DataType, Expr, FieldRef, FindMode, Op, SortKey, UnaryOp};
+use crate::builtins;
+use crate::value::Value;
+
+pub trait Bindings {
+	fn is_column(&self, table: &str, field: &str) -> bool;
+	fn column_type(&self, table: &str, field: &str) -> Option<DataType>;
+	fn variable(&self, name: &str) -> Option<Value>;
+	fn field(&self, name: &FieldRef) -> Option<Value>;
+	fn evaluate(&self, expr: &Expr) -> Option<Value>;
+	fn returns(&self, function: &str) -> Option<DataType>;
+}
+
+pub const ROWID_COLUMN: &str = "__rowid__";
+
+// User functions are registered with SQLite under a prefixed name so they
+// cannot shadow its built-in functions.
+pub fn function_name(name: &str) -> String {
+	format!("abl_fn_{}", name.to_lowercase().replace('-', "_"))
+}
+
+pub struct Query {
+	pub sql: String,
+	pub params: Vec<Value>,
+}
+
+pub struct IndexHint {
+	pub name: String,
+	pub columns: Vec<String>,
+}
+
+pub struct Source<'a> {
+	pub table: &'a str,
+	pub condition: Option<Expr>,
+	pub index: Option<&'a IndexHint>,
+	pub outer_join: bool,
+}
+
+// Positions a FIND in rowid order on the first source, either at one end
+// or relative to the record the buffer's cursor is on.
+#[derive(Clone, Copy)]
+pub enum Seek {
+	First,
+	Last,
+	After(i64),
+	Before(i64),
+}
+
+pub struct Select<'a> {
+	pub sources: Vec<Source<'a>>,
+	pub sort: &'a [SortKey],
+	pub seek: Option<Seek>,
+	pub limit: Option<usize>,
+}
+
+impl Select<'_> {
+	pub fn to_sql(&self, scope: &dyn Bindings) -> Option<Query> {
+		let tables: Vec<&str> = self.sources.iter().map(|s| s.table).collect();
+		let qualify = tables.len() > 1;
+		let mut params = Vec::new();
+		let columns = tables
+			.iter()
+			.map(|t| format!("\"{}\".rowid AS \"{}\", \"{}\".*", t, ROWID_COLUMN, t))
+			.collect::<Vec<_>>()
+			.join(", ");
+		let mut sql = format!("SELECT {} FROM", columns);
+		let mut where_clause = None;
+		for (i, source) in self.sources.iter().enumerate() {
+			if i > 0 {
+				sql.push_str(if source.outer_join { " LEFT JOIN" } else { " JOIN" });
+			}
+			sql.push_str(&format!(" \"{}\"", source.table));
+			if let Some(index) = source.index {
+				sql.push_str(&format!(" INDEXED BY \"{}\"", index.name));
+			}
+			// Unqualified names resolve against the phrase's own table first,
+			// then against the phrases joined before it.
+			let mut visible = vec![source.table];
+			visible.extend(&tables[..i]);
+			let resolver = Resolver { tables: visible, qualify, scope };
+			let condition = match &source.condition {
+				Some(expr) => Some(resolver.to_sql(expr, &mut params)?),
+				None => None,
+			};
+			if i == 0 {
+				let bound = match self.seek {
+					Some(Seek::After(rowid)) => Some((">", rowid)),
+					Some(Seek::Before(rowid)) => Some(("<", rowid)),
+					_ => None,
+				};
+				where_clause = match bound {
+					Some((op, rowid)) => {
+						params.push(Value::Integer(rowid));
+						let seek = format!("\"{}\".rowid {} ?", source.table, op);
+						Some(match condition {
+							Some(c) => format!("({}) AND {}", c, seek),
+							None => seek,
+						})
 					}
-				}
+					None => condition,
+				};
+			} else {
+				sql.push_str(&format!(" ON {}", condition.as_deref().unwrap_or("1")));
 			}
-			let mut sql = format!("SELECT * FROM \"{}\"", table);
-			if let Some(expr) = where_clause {
-				if let Some(w) = expr_to_sql(expr) {
-					sql.push_str(&format!(" WHERE {}", w));
-				}
+		}
+		if let Some(w) = where_clause {
+			sql.push_str(&format!(" WHERE {}", w));
+		}
+		let resolver = Resolver { tables: tables.clone(), qualify, scope };
+		let mut order = Vec::new();
+		for key in self.sort {
+			let mut e = resolver.to_sql(&key.expr, &mut params)?;
+			if resolver.is_text(&key.expr) {
+				e = format!("{} COLLATE ABL", e);
 			}
-			Some(sql)
+			order.push(if key.descending { format!("{} DESC", e) } else { e });
 		}
-		Statement::FindFirst { table, where_clause } => {
-			let mut sql = format!("SELECT * FROM \"{}\"", table);
-			if let Some(expr) = where_clause {
-				if let Some(w) = expr_to_sql(expr) {
-					sql.push_str(&format!(" WHERE {}", w));
+		if order.is_empty() {
+			for source in &self.sources {
+				if let Some(index) = source.index {
+					order.extend(index.columns.iter().map(|c| resolver.column(source.table, c)));
 				}
 			}
-			sql.push_str(" LIMIT 1");
-			Some(sql)
 		}
-		Statement::Create { table } => {
-			Some(format!("INSERT INTO \"{}\" DEFAULT VALUES", table))
+		if let Some(seek) = self.seek {
+			let descending = matches!(seek, Seek::Last | Seek::Before(_));
+			order.push(format!("\"{}\".rowid{}", tables[0], if descending { " DESC" } else { "" }));
 		}
-		Statement::Delete { table } => {
-			Some(format!("DELETE FROM \"{}\"", table))
+		if !order.is_empty() {
+			sql.push_str(&format!(" ORDER BY {}", order.join(", ")));
 		}
-		Statement::Display { fields } => {
-			if fields.iter().all(|f| f.starts_with('"') && f.ends_with('"')) {
-				let msg = fields.iter().map(|f| f.trim_matches('"')).collect::<Vec<_>>().join(" ");
-				println!("{}", msg);
-				None
-			} else {
-				None
-			}
+		if let Some(n) = self.limit {
+			sql.push_str(&format!(" LIMIT {}", n));
 		}
+		Some(Query { sql, params })
 	}
 }
 
-pub fn expr_to_sql(expr: &Expr) -> Option<String> {
-	match expr {
-		Expr::Identifier(s) => Some(format!("\"{}\"", s)),
-		Expr::String(s) => Some(format!("'{}'", s)),
-		Expr::Number(n) => Some(n.to_string()),
-		Expr::Float(f) => Some(f.to_string()),
-		Expr::Group(expr) => {
-			let inner = expr_to_sql(expr)?;
-			Some(format!("({})", inner))
+pub fn select_rowid_sql(table: &str) -> String {
+	format!("SELECT rowid AS \"{}\", * FROM \"{}\" WHERE rowid = ?", ROWID_COLUMN, table)
+}
+
+pub fn insert_sql(table: &str, columns: &[String]) -> String {
+	if columns.is_empty() {
+		return format!("INSERT INTO \"{}\" DEFAULT VALUES", table);
+	}
+	let names = columns.iter().map(|c| format!("\"{}\"", c)).collect::<Vec<_>>().join(", ");
+	let placeholders = vec!["?"; columns.len()].join(", ");
+	format!("INSERT INTO \"{}\" ({}) VALUES ({})", table, names, placeholders)
+}
+
+pub fn update_sql(table: &str, columns: &[String]) -> String {
+	let sets = columns.iter().map(|c| format!("\"{}\" = ?", c)).collect::<Vec<_>>().join(", ");
+	format!("UPDATE \"{}\" SET {} WHERE rowid = ?", table, sets)
+}
+
+pub fn delete_sql(table: &str) -> String {
+	format!("DELETE FROM \"{}\" WHERE rowid = ?", table)
+}
+
+enum Resolved {
+	// The column and its declared type, when the table is known.
+	Column(String, Option<DataType>),
+	Param(Value),
+}
+
+struct Resolver<'a> {
+	tables: Vec<&'a str>,
+	qualify: bool,
+	scope: &'a dyn Bindings,
+}
+
+impl Resolver<'_> {
+	fn column(&self, table: &str, field: &str) -> String {
+		if self.qualify {
+			format!("\"{}\".\"{}\"", table, field)
+		} else {
+			format!("\"{}\"", field)
+		}
+	}
+
+	// Names of the queried tables become columns; variables and fields of
+	// other buffers in scope are bound as parameters.
+	fn resolve(&self, name: &FieldRef) -> Resolved {
+		if let Some(buffer) = &name.table {
+			if let Some(table) = self.tables.iter().find(|t| t.eq_ignore_ascii_case(buffer)) {
+				let data_type = self.scope.column_type(table, &name.field);
+				return Resolved::Column(format!("\"{}\".\"{}\"", table, name.field), data_type);
+			}
+			return match self.scope.field(name) {
+				Some(value) => Resolved::Param(value),
+				None => Resolved::Column(format!("\"{}\".\"{}\"", buffer, name.field), None),
+			};
+		}
+		if let Some(value) = self.scope.variable(&name.field) {
+			return Resolved::Param(value);
 		}
-		Expr::BinOp { left, op, right } => {
-			let l = expr_to_sql(left)?;
-			let r = expr_to_sql(right)?;
-			match op {
-				Op::Eq => Some(format!("{} = {}", l, r)),
-				Op::Neq => Some(format!("{} <> {}", l, r)),
-				Op::Lt => Some(format!("{} < {}", l, r)),
-				Op::Gt => Some(format!("{} > {}", l, r)),
-				Op::Le => Some(format!("{} <= {}", l, r)),
-				Op::Ge => Some(format!("{} >= {}", l, r)),
-				Op::And => Some(format!("{} AND {}", l, r)),
-				Op::Or => Some(format!("{} OR {}", l, r)),
+		if let Some(table) = self.tables.iter().find(|t| self.scope.is_column(t, &name.field)) {
+			return Resolved::Column(self.column(table, &name.field), self.scope.column_type(table, &name.field));
+		}
+		match self.scope.field(name) {
+			Some(value) => Resolved::Param(value),
+			None => Resolved::Column(format!("\"{}\"", name.field), None),
+		}
+	}
+
+	fn to_sql(&self, expr: &Expr, params: &mut Vec<Value>) -> Option<String> {
+		match expr {
+			Expr::Identifier(s) => match self.resolve(s) {
+				Resolved::Column(column, _) => Some(column),
+				Resolved::Param(value) => {
+					params.push(value);
+					Some("?".to_string())
+				}
+			},
+			Expr::String(s) => Some(format!("'{}'", s.replace('\'', "''"))),
+			Expr::Number(n) => Some(n.to_string()),
+			// Keep the decimal point so SQLite reads the literal as REAL.
+			Expr::Decimal(d) if d.to_string().contains('.') => Some(d.to_string()),
+			Expr::Decimal(d) => Some(format!("{}.0", d)),
+			Expr::Logical(b) => Some(if *b { "1" } else { "0" }.to_string()),
+			Expr::Unknown => Some("NULL".to_string()),
+			Expr::Group(expr) => {
+				let inner = self.to_sql(expr, params)?;
+				Some(format!("({})", inner))
+			}
+			Expr::Date(_) | Expr::FirstOf(_) | Expr::LastOf(_) | Expr::Accum { .. } | Expr::Available(_) | Expr::Attribute { .. } => {
+				params.push(self.scope.evaluate(expr)?);
+				Some("?".to_string())
+			}
+			// The subquery sees its own table first and correlates with the
+			// tables of the enclosing query through qualified names.
+			Expr::CanFind { mode, table, where_clause } => {
+				let mut tables = vec![table.as_str()];
+				tables.extend(&self.tables);
+				let inner = Resolver { tables, qualify: true, scope: self.scope };
+				let mut sql = format!("SELECT 1 FROM \"{}\"", table);
+				if let Some(cond) = where_clause {
+					sql.push_str(&format!(" WHERE {}", inner.to_sql(cond, params)?));
+				}
+				Some(match mode {
+					FindMode::Unique => format!("(SELECT COUNT(*) FROM ({} LIMIT 2)) = 1", sql),
+					_ => format!("EXISTS ({})", sql),
+				})
+			}
+			Expr::Call { name, args } => {
+				let args = args.iter().map(|a| self.to_sql(a, params)).collect::<Option<Vec<_>>>()?;
+				let name = if builtins::is_function(name) { builtins::sql_name(name) } else { function_name(name) };
+				Some(format!("{}({})", name, args.join(", ")))
+			}
+			Expr::Unary { op, expr } => {
+				let inner = self.to_sql(expr, params)?;
+				match op {
+					UnaryOp::Neg => Some(format!("-({})", inner)),
+					UnaryOp::Not => Some(format!("NOT {}", inner)),
+				}
+			}
+			Expr::BinOp { left, op, right } => {
+				let l = self.to_sql(left, params)?;
+				let mut r = self.to_sql(right, params)?;
+				let comparison = matches!(op, Op::Eq | Op::Neq | Op::Lt | Op::Gt | Op::Le | Op::Ge);
+				if comparison && (self.is_text(left) || self.is_text(right)) {
+					r = format!("({}) COLLATE ABL", r);
+				}
+				// Dates are ISO text in SQLite, which `+` and `-` would read as
+				// numbers; integers move them by days or milliseconds.
+				match (op, self.temporal(left), self.temporal(right)) {
+					(Op::Add, Some(t), None) => return Some(shift(t, &l, &r)),
+					(Op::Add, None, Some(t)) => return Some(shift(t, &r, &l)),
+					(Op::Sub, Some(t), None) => return Some(shift(t, &l, &format!("-({})", r))),
+					(Op::Sub, Some(DataType::Date), Some(DataType::Date)) => {
+						return Some(format!("CAST(round(julianday({}) - julianday({})) AS INTEGER)", l, r));
+					}
+					(Op::Sub, Some(_), Some(_)) => {
+						return Some(format!("CAST(round((julianday({}) - julianday({})) * 86400000) AS INTEGER)", l, r));
+					}
+					_ => {}
+				}
+				match op {
+					Op::Add if self.is_text(left) || self.is_text(right) => Some(format!("{} || {}", l, r)),
+					Op::Add => Some(format!("{} + {}", l, r)),
+					Op::Sub => Some(format!("{} - {}", l, r)),
+					Op::Mul => Some(format!("{} * {}", l, r)),
+					Op::Div => Some(format!("CAST({} AS REAL) / {}", l, r)),
+					Op::Mod => Some(format!("abl_modulo({}, {})", l, r)),
+					Op::Begins => Some(format!("abl_begins({}, {})", l, r)),
+					Op::Matches => Some(format!("abl_matches({}, {})", l, r)),
+					Op::Contains => Some(format!("abl_contains({}, {})", l, r)),
+					// In ABL `? = ?` is true and `? <> x` is true, which is what
+					// SQLite's IS and IS NOT do with NULL.
+					Op::Eq => Some(format!("{} IS {}", l, r)),
+					Op::Neq => Some(format!("{} IS NOT {}", l, r)),
+					Op::Lt => Some(format!("{} < {}", l, r)),
+					Op::Gt => Some(format!("{} > {}", l, r)),
+					Op::Le => Some(format!("{} <= {}", l, r)),
+					Op::Ge => Some(format!("{} >= {}", l, r)),
+					Op::And => Some(format!("{} AND {}", l, r)),
+					Op::Or => Some(format!("{} OR {}", l, r)),
+				}
 			}
 		}
 	}
+
+	// DATE, DATETIME or DATETIME-TZ, for expressions known to be one.
+	fn temporal(&self, expr: &Expr) -> Option<DataType> {
+		let data_type = match expr {
+			Expr::Date(_) => Some(DataType::Date),
+			Expr::Identifier(s) => match self.resolve(s) {
+				Resolved::Column(_, data_type) => data_type,
+				Resolved::Param(Value::Date(_)) => Some(DataType::Date),
+				Resolved::Param(Value::DateTime(_)) => Some(DataType::DateTime),
+				Resolved::Param(Value::DateTimeTz(..)) => Some(DataType::DateTimeTz),
+				Resolved::Param(_) => None,
+			},
+			Expr::Group(inner) => self.temporal(inner),
+			Expr::Call { name, .. } => self.scope.returns(name),
+			Expr::BinOp { left, op: Op::Add, right } => self.temporal(left).or(self.temporal(right)),
+			Expr::BinOp { left, op: Op::Sub, right } if self.temporal(right).is_none() => self.temporal(left),
+			_ => None,
+		};
+		data_type.filter(|t| matches!(t, DataType::Date | DataType::DateTime | DataType::DateTimeTz))
+	}
+
+	// ABL overloads `+` for string concatenation, which SQLite spells `||`.
+	fn is_text(&self, expr: &Expr) -> bool {
+		match expr {
+			Expr::String(_) => true,
+			Expr::Identifier(s) => matches!(
+				self.resolve(s),
+				Resolved::Param(Value::Character(_)) | Resolved::Column(_, Some(DataType::Character))
+			),
+			Expr::Group(inner) => self.is_text(inner),
+			Expr::Call { name, .. } => self.scope.returns(name) == Some(DataType::Character),
+			Expr::BinOp { left, op: Op::Add, right } => self.is_text(left) || self.is_text(right),
+			_ => false,
+		}
+	}
+}
+
+// `date` plus a number of days, or `date` plus milliseconds for the
+// datetime types, in the ISO text the runtime stores.
+fn shift(data_type: DataType, date: &str, amount: &str) -> String {
+	match data_type {
+		DataType::Date => format!("date({}, CAST({} AS INTEGER) || ' days')", date, amount),
+		DataType::DateTimeTz => format!("strftime('%Y-%m-%dT%H:%M:%f+00:00', {}, (CAST({} AS INTEGER) / 1000.0) || ' seconds')", date, amount),
+		_ => format!("strftime('%Y-%m-%dT%H:%M:%f', {}, (CAST({} AS INTEGER) / 1000.0) || ' seconds')", date, amount),
+	}
 }
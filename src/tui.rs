@@ -12,8 +12,11 @@ use crossterm::{
     execute as cross_execute,
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
 };
+use crate::value::Value;
 
-pub fn display_results(cols: &[String], results: &[Vec<String>]) -> Result<(), Box<dyn Error>> {
+// `places` gives the DECIMALS of columns showing such variables, which
+// display that many decimal places.
+pub fn display_results(cols: &[String], places: &[Option<u32>], results: &[Vec<Value>]) -> Result<(), Box<dyn Error>> {
     if results.is_empty() {
         println!("No records found.");
         return Ok(());
@@ -25,11 +28,31 @@ pub fn display_results(cols: &[String], results: &[Vec<String>]) -> Result<(), B
     let backend = CrosstermBackend::new(stdout);
     let mut terminal = Terminal::new(backend)?;
 
+    // Columns holding only numbers (and unknowns) are right-aligned.
+    let numeric: Vec<bool> = (0..cols.len())
+        .map(|i| {
+            let mut known = results.iter().filter_map(|row| row.get(i)).filter(|v| **v != Value::Unknown).peekable();
+            known.peek().is_some() && known.all(|v| matches!(v, Value::Integer(_) | Value::Decimal(_)))
+        })
+        .collect();
+    let results: Vec<Vec<String>> = results
+        .iter()
+        .map(|row| {
+            row.iter()
+                .enumerate()
+                .map(|(i, v)| match (v, places.get(i).copied().flatten()) {
+                    (Value::Decimal(d), Some(places)) => d.to_fixed(places),
+                    _ => v.to_string(),
+                })
+                .collect()
+        })
+        .collect();
+
     let mut col_widths = vec![0; cols.len()];
     for (i, col) in cols.iter().enumerate() {
         col_widths[i] = col.len();
     }
-    for row in results {
+    for row in &results {
         for (i, val) in row.iter().enumerate() {
             if i < col_widths.len() {
                 col_widths[i] = col_widths[i].max(val.len());
@@ -41,16 +64,16 @@ pub fn display_results(cols: &[String], results: &[Vec<String>]) -> Result<(), B
     let mut separator_line = String::new();
     for (i, col_name) in cols.iter().enumerate() {
         let w = col_widths[i];
-        let cell = format!(" {:<w$} ", col_name);
+        let cell = if numeric[i] { format!(" {:>w$} ", col_name) } else { format!(" {:<w$} ", col_name) };
         header_line.push_str(&cell);
         
         for _ in 0..(w + 2) {
-            separator_line.push_str("─");
+            separator_line.push('─');
         }
 
         if i < cols.len() - 1 {
-            header_line.push_str("│");
-            separator_line.push_str("┼");
+            header_line.push('│');
+            separator_line.push('┼');
         }
     }
 
@@ -76,22 +99,26 @@ pub fn display_results(cols: &[String], results: &[Vec<String>]) -> Result<(), B
 
                 let mut content = String::new();
                 content.push_str(&header_line);
-                content.push_str("\n");
+                content.push('\n');
                 content.push_str(&separator_line);
-                content.push_str("\n");
+                content.push('\n');
 
                 for row in page_rows {
                     let mut data_line = String::new();
                     for (i, _) in cols.iter().enumerate() {
                         let w = col_widths[i];
-                        let val = row.get(i).map(|s| s.as_str()).unwrap_or("NULL");
-                        data_line.push_str(&format!(" {:<w$} ", val));
+                        let val = row.get(i).map(|s| s.as_str()).unwrap_or("");
+                        if numeric[i] {
+                            data_line.push_str(&format!(" {:>w$} ", val));
+                        } else {
+                            data_line.push_str(&format!(" {:<w$} ", val));
+                        }
                         if i < cols.len() - 1 {
-                            data_line.push_str("│");
+                            data_line.push('│');
                         }
                     }
                     content.push_str(&data_line);
-                    content.push_str("\n");
+                    content.push('\n');
                 }
 
                 let box_width = header_line.len() as u16 + 2;
@@ -0,0 +1,235 @@
+use std::cmp::Ordering;
+use std::fmt;
+use rusqlite::types::{Null, ToSqlOutput, Value as SqlValue};
+use rusqlite::ToSql;
+use crate::ast::DataType;
+use crate::date;
+use crate::decimal::{self, Decimal};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Character(String),
+    Integer(i64),
+    Decimal(Decimal),
+    Logical(bool),
+    // Days since 1970-01-01.
+    Date(i64),
+    // Milliseconds since 1970-01-01.
+    DateTime(i64),
+    // A UTC instant in milliseconds and its offset in minutes.
+    DateTimeTz(i64, i32),
+    // Bytes from a BLOB column, shown in hex.
+    Raw(Vec<u8>),
+    Unknown,
+}
+
+pub fn too_large() -> String {
+    "** Value too large for integer. (15747)".to_string()
+}
+
+impl Value {
+    pub fn initial(data_type: DataType) -> Value {
+        match data_type {
+            DataType::Character => Value::Character(String::new()),
+            DataType::Integer | DataType::Int64 => Value::Integer(0),
+            DataType::Decimal => Value::Decimal(Decimal::ZERO),
+            DataType::Logical => Value::Logical(false),
+            DataType::Date | DataType::DateTime | DataType::DateTimeTz => Value::Unknown,
+        }
+    }
+
+    pub fn coerce(self, data_type: DataType) -> Result<Value, String> {
+        match (data_type, self) {
+            (_, Value::Unknown) => Ok(Value::Unknown),
+            (DataType::Character, v) => Ok(Value::Character(v.to_string())),
+            (DataType::Date, Value::Date(d)) => Ok(Value::Date(d)),
+            (DataType::Date, Value::DateTime(ms)) => Ok(Value::Date(ms.div_euclid(date::MS_PER_DAY))),
+            (DataType::Date, Value::DateTimeTz(ms, offset)) => {
+                Ok(Value::Date((ms + i64::from(offset) * 60_000).div_euclid(date::MS_PER_DAY)))
+            }
+            (DataType::Date, Value::Character(s)) => date::parse_date(&s).map(Value::Date).ok_or_else(date::invalid),
+            (DataType::DateTime, Value::Date(d)) => Ok(Value::DateTime(d * date::MS_PER_DAY)),
+            (DataType::DateTime, Value::DateTime(ms)) => Ok(Value::DateTime(ms)),
+            (DataType::DateTime, Value::DateTimeTz(ms, offset)) => Ok(Value::DateTime(ms + i64::from(offset) * 60_000)),
+            (DataType::DateTime, Value::Character(s)) => date::parse_datetime(&s).map(Value::DateTime).ok_or_else(date::invalid),
+            (DataType::DateTimeTz, Value::Date(d)) => Ok(Value::DateTimeTz(d * date::MS_PER_DAY, 0)),
+            (DataType::DateTimeTz, Value::DateTime(ms)) => Ok(Value::DateTimeTz(ms, 0)),
+            (DataType::DateTimeTz, Value::DateTimeTz(ms, offset)) => Ok(Value::DateTimeTz(ms, offset)),
+            (DataType::DateTimeTz, Value::Character(s)) => {
+                date::parse_datetime_tz(&s).map(|(ms, offset)| Value::DateTimeTz(ms, offset)).ok_or_else(date::invalid)
+            }
+            (DataType::Integer | DataType::Int64, Value::Integer(i)) => Ok(Value::Integer(i)),
+            (DataType::Integer | DataType::Int64, Value::Decimal(d)) => {
+                d.to_int().map(Value::Integer).ok_or_else(too_large)
+            }
+            (DataType::Integer | DataType::Int64, Value::Character(s)) => s
+                .trim()
+                .parse::<i64>()
+                .map(Value::Integer)
+                .map_err(|_| format!("** Invalid character in numeric input {}. (76)", s)),
+            (DataType::Decimal, Value::Integer(i)) => Ok(Value::Decimal(Decimal::from_int(i))),
+            (DataType::Decimal, Value::Decimal(d)) => Ok(Value::Decimal(d)),
+            (DataType::Decimal, Value::Character(s)) => Decimal::parse(&s)
+                .map(Value::Decimal)
+                .ok_or_else(|| format!("** Invalid character in numeric input {}. (76)", s)),
+            (DataType::Logical, Value::Logical(b)) => Ok(Value::Logical(b)),
+            (DataType::Logical, Value::Character(s)) => match s.trim().to_lowercase().as_str() {
+                "yes" | "true" => Ok(Value::Logical(true)),
+                "no" | "false" => Ok(Value::Logical(false)),
+                _ => Err(format!("** Input value {} should be yes/no. (87)", s)),
+            },
+            _ => Err("** Incompatible data types in expression or assignment. (223)".to_string()),
+        }
+    }
+
+    pub fn is_true(&self) -> bool {
+        matches!(self, Value::Logical(true))
+    }
+
+    // Adding an integer moves a date by days and a datetime by milliseconds.
+    pub fn add(&self, other: &Value) -> Result<Value, String> {
+        match (self, other) {
+            (Value::Character(a), Value::Character(b)) => Ok(Value::Character(format!("{}{}", a, b))),
+            (Value::Integer(_), Value::Date(_) | Value::DateTime(_) | Value::DateTimeTz(..)) => other.add(self),
+            (Value::Date(d), Value::Integer(n)) => date::checked_days(d.checked_add(*n)).map(Value::Date),
+            (Value::DateTime(ms), Value::Integer(n)) => date::checked_ms(ms.checked_add(*n)).map(Value::DateTime),
+            (Value::DateTimeTz(ms, offset), Value::Integer(n)) => {
+                date::checked_ms(ms.checked_add(*n)).map(|ms| Value::DateTimeTz(ms, *offset))
+            }
+            _ => self.arithmetic(other, i64::checked_add, Decimal::checked_add),
+        }
+    }
+
+    // Subtracting two dates gives the days between them, two datetimes the
+    // milliseconds between them.
+    pub fn sub(&self, other: &Value) -> Result<Value, String> {
+        match (self, other) {
+            (Value::Date(d), Value::Integer(n)) => date::checked_days(d.checked_sub(*n)).map(Value::Date),
+            (Value::DateTime(ms), Value::Integer(n)) => date::checked_ms(ms.checked_sub(*n)).map(Value::DateTime),
+            (Value::DateTimeTz(ms, offset), Value::Integer(n)) => {
+                date::checked_ms(ms.checked_sub(*n)).map(|ms| Value::DateTimeTz(ms, *offset))
+            }
+            (Value::Date(a), Value::Date(b)) | (Value::DateTime(a), Value::DateTime(b)) => Ok(Value::Integer(a - b)),
+            (Value::DateTimeTz(a, _), Value::DateTimeTz(b, _)) => Ok(Value::Integer(a - b)),
+            _ => self.arithmetic(other, i64::checked_sub, Decimal::checked_sub),
+        }
+    }
+
+    pub fn mul(&self, other: &Value) -> Result<Value, String> {
+        self.arithmetic(other, i64::checked_mul, Decimal::checked_mul)
+    }
+
+    pub fn div(&self, other: &Value) -> Result<Value, String> {
+        match (self.as_decimal()?, other.as_decimal()?) {
+            (Some(_), Some(b)) if b.is_zero() => Err("** Attempt to divide by zero. (1307)".to_string()),
+            (Some(a), Some(b)) => a.checked_div(b).map(Value::Decimal).ok_or_else(decimal::too_large),
+            _ => Ok(Value::Unknown),
+        }
+    }
+
+    pub fn modulo(&self, other: &Value) -> Result<Value, String> {
+        match (self, other) {
+            (Value::Unknown, _) | (_, Value::Unknown) => Ok(Value::Unknown),
+            (_, Value::Integer(0)) => Err("** Attempt to divide by zero. (1307)".to_string()),
+            (Value::Integer(a), Value::Integer(b)) => a.checked_rem_euclid(*b).map(Value::Integer).ok_or_else(too_large),
+            _ => Err("** Incompatible data types in expression or assignment. (223)".to_string()),
+        }
+    }
+
+    pub fn negate(&self) -> Result<Value, String> {
+        match self {
+            Value::Integer(i) => i.checked_neg().map(Value::Integer).ok_or_else(too_large),
+            Value::Decimal(d) => d.checked_neg().map(Value::Decimal).ok_or_else(decimal::too_large),
+            Value::Unknown => Ok(Value::Unknown),
+            _ => Err("** Incompatible data types in expression or assignment. (223)".to_string()),
+        }
+    }
+
+    fn arithmetic(&self, other: &Value, int_op: fn(i64, i64) -> Option<i64>, dec_op: fn(Decimal, Decimal) -> Option<Decimal>) -> Result<Value, String> {
+        match (self, other) {
+            (Value::Integer(a), Value::Integer(b)) => int_op(*a, *b)
+                .map(Value::Integer)
+                .ok_or_else(too_large),
+            _ => match (self.as_decimal()?, other.as_decimal()?) {
+                (Some(a), Some(b)) => dec_op(a, b).map(Value::Decimal).ok_or_else(decimal::too_large),
+                _ => Ok(Value::Unknown),
+            },
+        }
+    }
+
+    fn as_decimal(&self) -> Result<Option<Decimal>, String> {
+        match self {
+            Value::Integer(i) => Ok(Some(Decimal::from_int(*i))),
+            Value::Decimal(d) => Ok(Some(*d)),
+            Value::Unknown => Ok(None),
+            _ => Err("** Incompatible data types in expression or assignment. (223)".to_string()),
+        }
+    }
+
+    pub fn compare(&self, other: &Value) -> Result<Option<Ordering>, String> {
+        match (self, other) {
+            (Value::Unknown, _) | (_, Value::Unknown) => Ok(None),
+            (Value::Integer(a), Value::Integer(b)) => Ok(Some(a.cmp(b))),
+            (Value::Integer(a), Value::Decimal(b)) => Ok(Some(Decimal::from_int(*a).cmp(b))),
+            (Value::Decimal(a), Value::Integer(b)) => Ok(Some(a.cmp(&Decimal::from_int(*b)))),
+            (Value::Decimal(a), Value::Decimal(b)) => Ok(Some(a.cmp(b))),
+            (Value::Character(a), Value::Character(b)) => Ok(Some(a.trim_end().to_lowercase().cmp(&b.trim_end().to_lowercase()))),
+            (Value::Logical(a), Value::Logical(b)) => Ok(Some(a.cmp(b))),
+            (Value::Raw(a), Value::Raw(b)) => Ok(Some(a.cmp(b))),
+            (Value::Date(a), Value::Date(b)) | (Value::DateTime(a), Value::DateTime(b)) => Ok(Some(a.cmp(b))),
+            (Value::DateTimeTz(a, _), Value::DateTimeTz(b, _)) => Ok(Some(a.cmp(b))),
+            (Value::Date(_), Value::DateTime(_)) => self.clone().coerce(DataType::DateTime)?.compare(other),
+            (Value::DateTime(_), Value::Date(_)) => self.compare(&other.clone().coerce(DataType::DateTime)?),
+            (Value::Date(_) | Value::DateTime(_), Value::DateTimeTz(..)) => self.clone().coerce(DataType::DateTimeTz)?.compare(other),
+            (Value::DateTimeTz(..), Value::Date(_) | Value::DateTime(_)) => self.compare(&other.clone().coerce(DataType::DateTimeTz)?),
+            _ => Err("** Incompatible data types in expression or assignment. (223)".to_string()),
+        }
+    }
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Value::Character(s) => write!(f, "{}", s),
+            Value::Integer(i) => write!(f, "{}", i),
+            Value::Decimal(d) => write!(f, "{}", d),
+            Value::Logical(b) => write!(f, "{}", if *b { "yes" } else { "no" }),
+            Value::Date(d) => write!(f, "{}", date::display_date(*d)),
+            Value::DateTime(ms) => write!(f, "{}", date::display_datetime(*ms)),
+            Value::DateTimeTz(ms, offset) => write!(f, "{}", date::display_datetime_tz(*ms, *offset)),
+            Value::Raw(bytes) => bytes.iter().try_for_each(|b| write!(f, "{:02x}", b)),
+            Value::Unknown => write!(f, "?"),
+        }
+    }
+}
+
+// A REAL beyond what DECIMAL can hold is an error, not a missing value.
+impl TryFrom<SqlValue> for Value {
+    type Error = String;
+
+    fn try_from(value: SqlValue) -> Result<Self, String> {
+        Ok(match value {
+            SqlValue::Null => Value::Unknown,
+            SqlValue::Integer(i) => Value::Integer(i),
+            SqlValue::Real(f) => Value::Decimal(Decimal::from_f64(f).ok_or_else(decimal::too_large)?),
+            SqlValue::Text(t) => Value::Character(t),
+            SqlValue::Blob(b) => Value::Raw(b),
+        })
+    }
+}
+
+impl ToSql for Value {
+    fn to_sql(&self) -> rusqlite::Result<ToSqlOutput<'_>> {
+        Ok(match self {
+            Value::Character(s) => ToSqlOutput::from(s.as_str()),
+            Value::Integer(i) => ToSqlOutput::from(*i),
+            Value::Decimal(d) => ToSqlOutput::from(d.to_f64()),
+            Value::Logical(b) => ToSqlOutput::from(*b),
+            Value::Date(d) => ToSqlOutput::from(date::iso_date(*d)),
+            Value::DateTime(ms) => ToSqlOutput::from(date::iso_datetime(*ms)),
+            Value::DateTimeTz(ms, _) => ToSqlOutput::from(date::iso_datetime_tz(*ms)),
+            Value::Raw(bytes) => ToSqlOutput::from(bytes.as_slice()),
+            Value::Unknown => ToSqlOutput::from(Null),
+        })
+    }
+}
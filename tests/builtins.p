@@ -0,0 +1,26 @@
+/* Built-in functions */
+DEFINE VARIABLE list AS CHARACTER INITIAL "red,green,blue" NO-UNDO.
+
+DISPLAY SUBSTRING("Progress", 4) SUBSTRING("Progress", 1, 3) INDEX("banana", "AN") INDEX("banana", "an", 3) R-INDEX("banana", "an").
+DISPLAY REPLACE("a-b-c", "-", "+") TRIM("  padded  ") LEFT-TRIM("xxabc", "x") CAPS("mixed Case") LC("MIXED").
+DISPLAY LENGTH("hello") FILL("ab", 3) ENTRY(2, list) NUM-ENTRIES(list) LOOKUP("BLUE", list) ENTRY(1, "a;b", ";").
+DISPLAY ROUND(3.14159, 2) TRUNCATE(3.14159, 2) ABS(-7) MAXIMUM(3, 9, 4) MINIMUM(3, 9, 4).
+DISPLAY STRING(42) + "!" INTEGER("12") + 1 DECIMAL("2.5") * 2 LOGICAL("yes").
+
+IF RANDOM(1, 6) >= 1 AND RANDOM(1, 6) <= 6 THEN
+    DISPLAY "RANDOM stays in range".
+
+/* Pushed into SQLite when used in WHERE */
+FOR EACH Customer WHERE LENGTH(Name) = 4 AND SUBSTRING(Name, 2, 1) <> "a":
+    DISPLAY Name CAPS(Name).
+END.
+
+FOR EACH Order WHERE ROUND(Amount / 100, 0) = 2:
+    DISPLAY OrderId Amount.
+END.
+
+/* ACCUMULATE keeps its own parenthesised options */
+FOR EACH Order:
+    ACCUMULATE Amount (TOTAL MAXIMUM).
+END.
+DISPLAY (ACCUM TOTAL Amount) (ACCUM MAXIMUM Amount).
@@ -0,0 +1,16 @@
+/* CAN-FIND existence checks */
+FOR EACH Customer:
+    IF CAN-FIND(FIRST Order WHERE Order.CustomerId = Customer.Id) THEN
+        DISPLAY Name.
+END.
+
+FOR EACH Customer WHERE NOT CAN-FIND(FIRST Order WHERE Order.CustomerId = Customer.Id):
+    DISPLAY Name.
+END.
+
+FOR EACH Customer WHERE CAN-FIND(Order WHERE CustomerId = Id AND Amount > 120):
+    DISPLAY Name.
+END.
+
+IF NOT CAN-FIND(Order WHERE CustomerId = 1) THEN
+    DISPLAY "Customer 1 has more than one order".
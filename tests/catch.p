@@ -0,0 +1,54 @@
+/* CATCH, FINALLY, UNDO THROW and RETURN ERROR */
+DO ON ERROR UNDO, LEAVE:
+    FIND FIRST Customer WHERE Name = "Nobody".
+    DISPLAY "Not reached".
+    CATCH err AS Progress.Lang.SysError:
+        DISPLAY err:GetMessage(1).
+        DISPLAY err:GetMessageNum(1).
+    END CATCH.
+    FINALLY:
+        DISPLAY "Finally after SysError".
+    END FINALLY.
+END.
+
+DO ON ERROR UNDO, LEAVE:
+    CREATE Customer.
+    ASSIGN Customer.Name = "Temp".
+    RETURN ERROR "Custom failure".
+    CATCH err AS Progress.Lang.AppError:
+        DISPLAY err:ReturnValue.
+        DISPLAY err:NumMessages.
+    END CATCH.
+END.
+
+IF NOT CAN-FIND(FIRST Customer WHERE Name = "Temp") THEN
+    DISPLAY "Caught block was undone".
+
+DO ON ERROR UNDO, LEAVE:
+    DO ON ERROR UNDO, THROW:
+        CREATE Customer.
+        ASSIGN Customer.Id = 1.
+    END.
+    CATCH err AS Progress.Lang.Error:
+        DISPLAY err:GetMessage(1).
+        DISPLAY err:GetMessageNum(1).
+    END CATCH.
+END.
+
+DO ON ERROR UNDO, LEAVE:
+    DO ON ERROR UNDO, LEAVE:
+        FIND LAST Order WHERE Amount > 1000.
+        CATCH inner AS Progress.Lang.Error:
+            DISPLAY "Rethrowing".
+            UNDO, THROW inner.
+        END CATCH.
+    END.
+    CATCH outer AS Progress.Lang.Error:
+        DISPLAY outer:GetMessage(1).
+    END CATCH.
+END.
+
+DO ON ERROR UNDO, LEAVE:
+    FIND FIRST Order WHERE Amount < 0.
+END.
+DISPLAY "Continued after ON ERROR UNDO, LEAVE".
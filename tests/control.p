@@ -0,0 +1,18 @@
+/* Conditional logic and DO blocks */
+DEFINE VARIABLE limit AS DECIMAL NO-UNDO INITIAL 150.
+
+FOR EACH Order:
+    IF Amount > limit THEN DO:
+        DISPLAY "Large order".
+        DISPLAY OrderId Amount.
+    END.
+    ELSE IF Amount = limit THEN
+        DISPLAY "Order at limit".
+    ELSE
+        DISPLAY "Small order".
+END.
+
+FOR EACH Customer:
+    IF Name = "john" OR Name = "Peter" THEN
+        DISPLAY Name.
+END.
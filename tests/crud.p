@@ -2,16 +2,17 @@
 
 /* 1. Create new customer */
 CREATE Customer.
+ASSIGN Customer.Name = "Anna".
 
-/* 2. Find the most recent customer */
-FIND FIRST Customer WHERE Id > 0:
-    DISPLAY "New customer created".
-END.
+/* 2. Find the new customer and rename it */
+FIND FIRST Customer WHERE Name = "Anna".
+Customer.Name = "Annie".
+DISPLAY "Customer renamed".
 
-/* 3. Cleanup/Delete */
+/* 3. Delete only the record found above */
 DELETE Customer.
 
-/* 4. Verify empty table */
+/* 4. Verify the remaining customers */
 FOR EACH Customer:
     DISPLAY Name.
 END.
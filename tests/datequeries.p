@@ -0,0 +1,51 @@
+/* Date arithmetic in WHERE picks the same rows as the same test in IF */
+DEFINE VARIABLE cutoff AS DATE INITIAL 02/01/2025 NO-UNDO.
+DEFINE VARIABLE stamp AS DATETIME NO-UNDO.
+DEFINE VARIABLE later AS DATETIME NO-UNDO.
+DEFINE VARIABLE zoned AS DATETIME-TZ NO-UNDO.
+DEFINE VARIABLE earlier AS DATETIME-TZ NO-UNDO.
+
+FOR EACH Order WHERE OrderDate + 30 > 03/20/2025:
+    DISPLAY OrderId OrderDate + 30.
+END.
+FOR EACH Order:
+    IF OrderDate + 30 > 03/20/2025 THEN
+        DISPLAY OrderId OrderDate + 30.
+END.
+
+FOR EACH Order WHERE OrderDate - 14 < cutoff + 19 AND 1 + OrderDate <> 01/16/2025:
+    DISPLAY OrderId OrderDate - 14.
+END.
+FOR EACH Order:
+    IF OrderDate - 14 < cutoff + 19 AND 1 + OrderDate <> 01/16/2025 THEN
+        DISPLAY OrderId OrderDate - 14.
+END.
+
+FOR EACH Order WHERE OrderDate - cutoff > 20:
+    DISPLAY OrderId OrderDate - cutoff.
+END.
+FOR EACH Order:
+    IF OrderDate - cutoff > 20 THEN
+        DISPLAY OrderId OrderDate - cutoff.
+END.
+
+/* DATETIME and DATETIME-TZ move by milliseconds */
+stamp = "2025-02-27T23:59:59.500".
+later = "2025-02-28T00:00:01.500".
+FOR EACH Order WHERE stamp + OrderId * 1000 >= later:
+    DISPLAY OrderId stamp + OrderId * 1000.
+END.
+FOR EACH Order:
+    IF stamp + OrderId * 1000 >= later THEN
+        DISPLAY OrderId stamp + OrderId * 1000.
+END.
+
+zoned = "2025-02-28T01:00:00+02:00".
+earlier = "2025-02-27T21:30:00Z".
+FOR EACH Order WHERE zoned - OrderId * 3600000 > earlier:
+    DISPLAY OrderId.
+END.
+FOR EACH Order:
+    IF zoned - OrderId * 3600000 > earlier THEN
+        DISPLAY OrderId.
+END.
@@ -0,0 +1,47 @@
+/* DATE, DATETIME and DATETIME-TZ values */
+DEFINE VARIABLE due AS DATE INITIAL 12/31/2025 NO-UNDO.
+DEFINE VARIABLE cutoff AS DATE INITIAL 02/01/2025 NO-UNDO.
+DEFINE VARIABLE stamp AS DATETIME NO-UNDO.
+DEFINE VARIABLE zoned AS DATETIME-TZ NO-UNDO.
+
+DISPLAY due due + 1 due - 12/25/2025 DAY(due) MONTH(due) YEAR(due) WEEKDAY(due).
+DISPLAY DATE(2, 29, 2024) DATE("03/15/2025") 01/01/2026 > due.
+
+stamp = "2025-06-01T08:30:00".
+zoned = "2025-06-01T08:30:00-05:00".
+DISPLAY stamp stamp + 90000 zoned DATE(zoned).
+
+IF TODAY > 01/01/2020 AND NOW > stamp THEN
+    DISPLAY "TODAY and NOW read the clock".
+
+/* Stored as ISO text, so SQLite compares them in date order */
+FOR EACH Order WHERE OrderDate >= cutoff BY OrderDate DESCENDING:
+    DISPLAY OrderId OrderDate OrderDate - cutoff.
+END.
+
+FOR EACH Order WHERE MONTH(OrderDate) = 1:
+    DISPLAY OrderId OrderDate.
+END.
+
+FIND FIRST Order WHERE OrderId = 1.
+Order.OrderDate = Order.OrderDate + 30.
+FIND FIRST Order WHERE OrderDate = 02/14/2025.
+DISPLAY OrderId OrderDate.
+
+/* Dates stay within 1/1/0001 to 12/31/9999 */
+ASSIGN due = due + 9223372036854775807 NO-ERROR.
+DISPLAY ERROR-STATUS:GET-MESSAGE(1) due.
+ASSIGN stamp = stamp - 9223372036854775807 NO-ERROR.
+DISPLAY ERROR-STATUS:GET-MESSAGE(1) stamp.
+DISPLAY DATE(12, 31, 9999) DATE(1, 1, 1).
+
+/* The first and last dates are stored and read back as dates */
+FIND FIRST Order WHERE OrderId = 1.
+Order.OrderDate = 12/31/9999.
+FIND FIRST Order WHERE OrderId = 2.
+Order.OrderDate = DATE(1, 1, 1).
+FOR EACH Order WHERE OrderDate < 01/01/1000 OR OrderDate > 01/01/9000 BY OrderDate:
+    DISPLAY OrderId OrderDate OrderDate - 01/01/2000.
+END.
+Order.OrderDate = Order.OrderDate - 1 NO-ERROR.
+DISPLAY ERROR-STATUS:GET-MESSAGE(1) OrderDate.
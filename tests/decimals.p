@@ -0,0 +1,36 @@
+/* Fixed-point DECIMAL arithmetic */
+DEFINE VARIABLE price AS DECIMAL DECIMALS 2 NO-UNDO.
+DEFINE VARIABLE rate AS DECIMAL INITIAL 0.1 NO-UNDO.
+DEFINE VARIABLE total AS DECIMAL NO-UNDO.
+DEFINE VARIABLE i AS INTEGER NO-UNDO.
+
+/* 0.1 + 0.2 is exactly 0.3, and ten dimes make a dollar */
+DISPLAY 0.1 + 0.2 0.1 + 0.2 = 0.3 1 / 3 2 / 3 10 / 4.
+DO i = 1 TO 10:
+    total = total + rate.
+END.
+DISPLAY total total = 1.
+
+/* DECIMALS 2 rounds every assignment half away from zero */
+price = 19.995.
+DISPLAY price.
+price = -2.345.
+DISPLAY price price * 3 ROUND(2.5, 0) ROUND(-2.5, 0) TRUNCATE(2.789, 1).
+price = 20.
+DISPLAY price.
+
+DISPLAY INTEGER(2.5) DECIMAL("12.3456789012345") 12345678901234567.25 * 4.
+
+/* Amounts bind to SQLite as REAL and read back exactly */
+FOR EACH Order WHERE Amount * 1.1 > 150.0:
+    DISPLAY OrderId Amount Amount * 1.1 Amount / 3.
+END.
+
+FIND FIRST Order WHERE OrderId = 1.
+Order.Amount = Order.Amount + 0.07.
+FIND FIRST Order WHERE Amount = 100.07.
+DISPLAY OrderId Amount.
+
+/* A REAL too large for DECIMAL is an error, not an unknown value */
+FIND FIRST Order WHERE ABS(Amount * 1e20 * 1e20) > 0 NO-ERROR.
+DISPLAY ERROR-STATUS:GET-MESSAGE(1).
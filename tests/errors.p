@@ -0,0 +1,17 @@
+/* NO-ERROR, AVAILABLE and ERROR-STATUS */
+FIND FIRST Customer WHERE Name = "Nobody" NO-ERROR.
+IF NOT AVAILABLE Customer THEN
+    DISPLAY "No customer called Nobody".
+IF ERROR-STATUS:ERROR THEN
+    DISPLAY ERROR-STATUS:NUM-MESSAGES ERROR-STATUS:GET-MESSAGE(1).
+
+FIND Order WHERE CustomerId = 1 NO-ERROR.
+IF ERROR-STATUS:ERROR THEN
+    DISPLAY ERROR-STATUS:GET-MESSAGE(1).
+
+FIND Customer 1 NO-ERROR.
+IF AVAILABLE Customer AND NOT ERROR-STATUS:ERROR THEN
+    DISPLAY Customer.Name.
+
+DELETE Order NO-ERROR.
+DISPLAY ERROR-STATUS:GET-MESSAGE(1).
@@ -0,0 +1,38 @@
+/* Arithmetic, concatenation and operator precedence */
+DEFINE VARIABLE total AS DECIMAL NO-UNDO.
+DEFINE VARIABLE n AS INTEGER NO-UNDO.
+DEFINE VARIABLE label AS CHARACTER NO-UNDO.
+
+FOR EACH Order WHERE Amount * 2 > 250 OR CustomerId = 1 AND OrderId MODULO 2 = 1:
+    total = total + Amount.
+    n = n + 1.
+END.
+label = "Orders: " + "counted".
+DISPLAY label n total total / n.
+
+DO WHILE n > 0:
+    n = n - 1.
+END.
+IF NOT n > 0 AND -total < 0 THEN
+    DISPLAY "Precedence OK".
+
+/* A minus after an operand subtracts, even without a blank after it */
+n = 6.
+n = n -1.
+DISPLAY n n -1 -n.
+
+/* MODULO is never negative, in WHERE clauses as in expressions */
+FOR EACH Order WHERE (OrderId - 5) MODULO 3 = 2:
+    DISPLAY OrderId (OrderId - 5) MODULO 3.
+END.
+
+/* + on a text column concatenates in WHERE clauses too */
+FOR EACH Customer WHERE Name + Name = "JohnJohn":
+    DISPLAY Id Name + Name.
+END.
+
+/* MODULO of the smallest INTEGER by -1 overflows */
+ASSIGN n = (-9223372036854775807 - 1) MODULO -1 NO-ERROR.
+DISPLAY ERROR-STATUS:GET-MESSAGE(1).
+FIND FIRST Order WHERE (OrderId - 9223372036854775807 - 2) MODULO -1 = 0 NO-ERROR.
+DISPLAY ERROR-STATUS:GET-MESSAGE(1).
@@ -0,0 +1,14 @@
+/* RUN of external programs found on the PROPATH */
+DEFINE VARIABLE r AS INTEGER NO-UNDO.
+DEFINE VARIABLE factor AS INTEGER INITIAL 100 NO-UNDO.
+
+RUN lib/calc.p (INPUT 5, OUTPUT r).
+DISPLAY r.
+
+RUN lib/calc.p (INPUT r, OUTPUT r).
+DISPLAY r factor.
+
+FOR EACH Customer:
+    RUN lib/order-count.p (INPUT Id, OUTPUT r).
+    DISPLAY Name r.
+END.
@@ -0,0 +1,25 @@
+/* FIND FIRST / LAST / NEXT / PREV and unique FIND */
+FIND LAST Customer.
+DISPLAY Id Name.
+
+FIND PREV Customer.
+DISPLAY Id Name.
+
+FIND NEXT Customer.
+DISPLAY Id Name.
+
+FIND NEXT Customer NO-ERROR.
+IF NOT AVAILABLE Customer THEN DISPLAY "Past the last customer".
+
+FIND PREV Customer.
+DISPLAY Id Name.
+
+FIND FIRST Order WHERE CustomerId = 1.
+FIND NEXT Order WHERE CustomerId = 1.
+DISPLAY OrderId Amount.
+
+FIND Customer 2.
+DISPLAY Id Name.
+
+FIND Customer WHERE Name = "John".
+DISPLAY Id Name.
@@ -0,0 +1,75 @@
+/* User-defined functions */
+FUNCTION doubled RETURNS DECIMAL (INPUT amount-in AS DECIMAL) FORWARD.
+
+DEFINE VARIABLE threshold AS DECIMAL INITIAL 250 NO-UNDO.
+
+FUNCTION fullName RETURNS CHARACTER (INPUT first-name AS CHARACTER, INPUT last-name AS CHARACTER):
+    RETURN first-name + " " + last-name.
+END FUNCTION.
+
+FUNCTION factorial RETURNS INTEGER (INPUT n AS INTEGER):
+    IF n <= 1 THEN RETURN 1.
+    RETURN n * factorial(n - 1).
+END FUNCTION.
+
+FUNCTION label-for RETURNS CHARACTER (INPUT id AS INTEGER):
+    DEFINE VARIABLE result AS CHARACTER NO-UNDO.
+    IF id = 1 THEN result = "first".
+    ELSE result = "other".
+    RETURN result + " " + fullName("customer", "#").
+END FUNCTION.
+
+DISPLAY fullName("Mary", "Smith").
+DISPLAY factorial(6).
+DISPLAY label-for(1).
+DISPLAY label-for(2).
+
+/* Pushed into SQLite as a registered function */
+FOR EACH Order WHERE doubled(Amount) >= threshold:
+    DISPLAY OrderId Amount.
+END.
+
+FOR EACH Customer WHERE fullName(Name, "Doe") BEGINS "P":
+    DISPLAY Name.
+END.
+
+FOR EACH Order WHERE CustomerId = 1 BY doubled(Amount) DESCENDING:
+    DISPLAY OrderId doubled(Amount).
+END.
+
+FIND FIRST Customer WHERE Id > 1 AND factorial(Id) > 2.
+DISPLAY Name.
+IF CAN-FIND(FIRST Order WHERE doubled(Amount) = 400) THEN
+    DISPLAY "Found the 200 order".
+
+FOR EACH Customer, EACH Order OF Customer OUTER-JOIN WHERE doubled(Amount) > 250:
+    IF AVAILABLE Order THEN DISPLAY Customer.Name Order.OrderId.
+    ELSE DISPLAY Customer.Name.
+END.
+
+/* A function SQLite calls can run a query calling another */
+FUNCTION has-big-order RETURNS LOGICAL (INPUT id AS INTEGER):
+    RETURN CAN-FIND(FIRST Order WHERE Order.CustomerId = id AND doubled(Amount) > 350).
+END FUNCTION.
+
+FOR EACH Customer WHERE has-big-order(Id):
+    DISPLAY Name.
+END.
+
+FUNCTION checked RETURNS INTEGER (INPUT n AS INTEGER):
+    IF n > 2 THEN RETURN ERROR "Too many customers".
+    RETURN n.
+END FUNCTION.
+
+DO ON ERROR UNDO, LEAVE:
+    FOR EACH Customer WHERE checked(Id) > 0:
+        DISPLAY Name.
+    END.
+    CATCH err AS Progress.Lang.AppError:
+        DISPLAY err:ReturnValue.
+    END CATCH.
+END.
+
+FUNCTION doubled RETURNS DECIMAL (INPUT amount-in AS DECIMAL):
+    RETURN amount-in * 2.
+END FUNCTION.
@@ -0,0 +1,14 @@
+/* OF joins and multi-buffer FOR EACH */
+FOR EACH Customer:
+    FOR EACH Order OF Customer:
+        DISPLAY Customer.Name Order.OrderId Order.Amount.
+    END.
+END.
+
+FOR EACH Customer, EACH Order WHERE Order.CustomerId = Customer.Id BY Order.Amount:
+    DISPLAY Name OrderId Amount.
+END.
+
+FOR EACH Customer, EACH Order OF Customer OUTER-JOIN:
+    DISPLAY Customer.Name.
+END.
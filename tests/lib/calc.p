@@ -0,0 +1,12 @@
+/* Shared calculations for tests/external.p */
+DEFINE INPUT PARAMETER n AS INTEGER NO-UNDO.
+DEFINE OUTPUT PARAMETER result AS INTEGER NO-UNDO.
+DEFINE VARIABLE factor AS INTEGER INITIAL 3 NO-UNDO.
+
+RUN triple (INPUT n, OUTPUT result).
+
+PROCEDURE triple:
+    DEFINE INPUT PARAMETER x AS INTEGER NO-UNDO.
+    DEFINE OUTPUT PARAMETER y AS INTEGER NO-UNDO.
+    y = x * factor.
+END PROCEDURE.
@@ -0,0 +1,7 @@
+/* Counts a customer's orders */
+DEFINE INPUT PARAMETER customer-id AS INTEGER NO-UNDO.
+DEFINE OUTPUT PARAMETER orders AS INTEGER NO-UNDO.
+
+FOR EACH Order WHERE CustomerId = customer-id:
+    orders = orders + 1.
+END.
@@ -0,0 +1,30 @@
+/* LOGICAL values and the unknown value */
+DEFINE VARIABLE found AS LOGICAL INITIAL YES NO-UNDO.
+DEFINE VARIABLE limit AS INTEGER INITIAL ? NO-UNDO.
+DEFINE VARIABLE nobody AS CHARACTER NO-UNDO.
+
+DISPLAY found NOT found TRUE AND FALSE NO OR YES.
+DISPLAY limit limit = ? limit <> ? limit + 1 limit > 5.
+
+/* Unknown only decides AND and OR when the other operand does not */
+DISPLAY (FALSE AND ?) (TRUE AND ?) (TRUE OR ?) (FALSE OR ?).
+
+IF limit > 5 THEN DISPLAY "unknown is not true".
+ELSE DISPLAY "unknown is not true, so ELSE runs".
+
+CREATE Customer.
+nobody = ?.
+
+/* x = ? finds the NULL rows, x <> "John" includes them */
+FOR EACH Customer WHERE Name = ?:
+    DISPLAY Id Name.
+END.
+FOR EACH Customer WHERE Name = nobody:
+    DISPLAY Id Name.
+END.
+FOR EACH Customer WHERE Name <> "John":
+    DISPLAY Id Name.
+END.
+FOR EACH Customer WHERE Name <> ? AND Id > 1:
+    DISPLAY Id Name.
+END.
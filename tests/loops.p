@@ -0,0 +1,43 @@
+/* Iterative blocks, LEAVE and NEXT */
+DEFINE VARIABLE i AS INTEGER NO-UNDO.
+
+DO i = 1 TO 10 BY 3:
+    IF i = 7 THEN NEXT.
+    DISPLAY i.
+END.
+
+DO i = 3 TO 1 BY -1:
+    DISPLAY "Countdown".
+END.
+
+outer:
+FOR EACH Customer:
+    FOR EACH Order WHERE CustomerId = Customer.Id:
+        IF Amount > 120 THEN DO:
+            DISPLAY "Found a large order, stopping".
+            LEAVE outer.
+        END.
+    END.
+END.
+
+REPEAT:
+    DISPLAY "Repeat runs once".
+    LEAVE.
+END.
+
+/* Loops that run to either end of the INTEGER range stop there */
+DO i = 9223372036854775806 TO 9223372036854775807:
+    DISPLAY i.
+END.
+DO i = -9223372036854775807 TO -9223372036854775807 - 1 BY -1:
+    DISPLAY i.
+END.
+
+DO ON ERROR UNDO, LEAVE:
+    DO i = 1 TO 3 BY 0:
+        DISPLAY "BY 0 never runs".
+    END.
+    CATCH e AS Progress.Lang.Error:
+        DISPLAY e:GetMessage(1).
+    END CATCH.
+END.
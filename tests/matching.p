@@ -0,0 +1,14 @@
+/* String match operators */
+FOR EACH Customer WHERE Name BEGINS "jo" OR Name MATCHES "*er":
+    DISPLAY Name.
+END.
+
+FOR EACH Customer:
+    IF Name MATCHES "M.ry" THEN DISPLAY "Matched Mary".
+    IF Name + " Smith" CONTAINS "smi*" THEN DISPLAY Name.
+END.
+
+/* = and < ignore case and trailing blanks in WHERE clauses as in IF */
+FOR EACH Customer WHERE Name = "john  " OR Name < "MARY":
+    DISPLAY Id Name.
+END.
@@ -0,0 +1,57 @@
+/* Internal procedures and RUN with parameters */
+DEFINE VARIABLE total AS DECIMAL NO-UNDO.
+DEFINE VARIABLE greeting AS CHARACTER NO-UNDO.
+DEFINE VARIABLE counter AS INTEGER INITIAL 1 NO-UNDO.
+
+RUN greet (INPUT "Mary", OUTPUT greeting).
+DISPLAY greeting.
+
+RUN customer-total (INPUT 1, OUTPUT total).
+DISPLAY total.
+
+RUN bump (INPUT-OUTPUT counter).
+RUN bump (INPUT-OUTPUT counter).
+DISPLAY counter.
+
+RUN factorial (INPUT 5, OUTPUT total).
+DISPLAY total.
+
+RUN show-orders.
+
+PROCEDURE greet:
+    DEFINE INPUT PARAMETER who AS CHARACTER NO-UNDO.
+    DEFINE OUTPUT PARAMETER result AS CHARACTER NO-UNDO.
+    result = "Hello, " + who.
+END PROCEDURE.
+
+PROCEDURE customer-total:
+    DEFINE INPUT PARAMETER customer-id AS INTEGER NO-UNDO.
+    DEFINE OUTPUT PARAMETER sum AS DECIMAL NO-UNDO.
+    FOR EACH Order WHERE CustomerId = customer-id:
+        sum = sum + Amount.
+    END.
+END PROCEDURE.
+
+PROCEDURE bump:
+    DEFINE INPUT-OUTPUT PARAMETER n AS INTEGER NO-UNDO.
+    DEFINE VARIABLE step AS INTEGER INITIAL 10 NO-UNDO.
+    n = n + step.
+END PROCEDURE.
+
+PROCEDURE factorial:
+    DEFINE INPUT PARAMETER n AS INTEGER NO-UNDO.
+    DEFINE OUTPUT PARAMETER result AS DECIMAL NO-UNDO.
+    DEFINE VARIABLE rest AS DECIMAL NO-UNDO.
+    IF n <= 1 THEN DO:
+        result = 1.
+        RETURN.
+    END.
+    RUN factorial (INPUT n - 1, OUTPUT rest).
+    result = n * rest.
+END PROCEDURE.
+
+PROCEDURE show-orders:
+    FOR EACH Order WHERE Amount > counter * 5:
+        DISPLAY OrderId Amount.
+    END.
+END PROCEDURE.
@@ -0,0 +1,10 @@
+/* Table- and database-qualified field references */
+FOR EACH Customer WHERE Customer.Id < 3:
+    FOR EACH Order WHERE Order.CustomerId = Customer.Id AND Order.Amount > 0:
+        DISPLAY Customer.Name Order.OrderId test.Order.Amount.
+    END.
+END.
+
+FIND FIRST Customer WHERE Customer.Name = "Mary".
+Customer.Name = "Maria".
+DISPLAY Customer.Id test.Customer.Name.
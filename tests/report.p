@@ -0,0 +1,37 @@
+/* Subtotal report with BREAK BY */
+FOR EACH Order BREAK BY CustomerId BY OrderId:
+    ACCUMULATE Amount (TOTAL COUNT AVERAGE BY CustomerId).
+    IF FIRST-OF(CustomerId) THEN
+        DISPLAY "New customer group".
+    IF LAST-OF(CustomerId) THEN
+        DISPLAY CustomerId (ACCUM COUNT BY CustomerId Amount) (ACCUM TOTAL BY CustomerId Amount) (ACCUM AVERAGE BY CustomerId Amount).
+END.
+DISPLAY (ACCUM TOTAL Amount).
+
+FOR EACH Order:
+    ACCUMULATE Order.OrderId (MAXIMUM COUNT).
+END.
+DISPLAY (ACCUM MAXIMUM OrderId) (ACCUM COUNT Order.OrderId).
+
+/* Groups follow ABL's case-insensitive text comparison */
+CREATE Customer.
+Customer.Name = "acme".
+CREATE Customer.
+Customer.Name = "Bolt".
+CREATE Customer.
+Customer.Name = "ACME".
+FOR EACH Customer BREAK BY Name:
+    ACCUMULATE Id (COUNT BY Name).
+    IF LAST-OF(Name) THEN
+        DISPLAY Name (ACCUM COUNT BY Name Id).
+END.
+
+/* A procedure starts its totals afresh on every RUN */
+PROCEDURE total-orders:
+    FOR EACH Order:
+        ACCUMULATE Amount (TOTAL).
+    END.
+    DISPLAY (ACCUM TOTAL Amount).
+END PROCEDURE.
+RUN total-orders.
+RUN total-orders.
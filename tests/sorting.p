@@ -0,0 +1,8 @@
+/* Sorting phrases */
+FOR EACH Order BY Amount DESCENDING BY OrderId:
+    DISPLAY OrderId Amount.
+END.
+
+FOR EACH Order WHERE Amount > 0 USE-INDEX OrderCustomer:
+    DISPLAY CustomerId OrderId.
+END.
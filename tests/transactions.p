@@ -0,0 +1,30 @@
+/* Transactions, UNDO and rollback */
+DO TRANSACTION:
+    CREATE Customer.
+    ASSIGN Customer.Name = "Temp".
+    UNDO, LEAVE.
+END.
+
+IF NOT CAN-FIND(FIRST Customer WHERE Name = "Temp") THEN
+    DISPLAY "Undone customer was not kept".
+
+FOR EACH Order:
+    Amount = Amount * 2.
+    IF OrderId = 2 THEN UNDO, NEXT.
+END.
+
+FOR EACH Order:
+    DISPLAY OrderId Amount.
+END.
+
+outer:
+DO TRANSACTION:
+    FOR EACH Customer:
+        Name = Name + "!".
+        IF Id = 2 THEN UNDO outer, LEAVE outer.
+    END.
+END.
+
+FOR EACH Customer:
+    DISPLAY Name.
+END.
@@ -0,0 +1,13 @@
+/* Typed values reach the display: numbers right-align and the unknown
+   value is shown as ? rather than the text of a string */
+CREATE Customer.
+Customer.Name = "NULL".
+CREATE Customer.
+
+FOR EACH Customer WHERE Id > 2:
+    DISPLAY Id Name Name = ? LENGTH(Name).
+END.
+
+FOR EACH Order:
+    DISPLAY OrderId CustomerId Amount OrderDate.
+END.
@@ -0,0 +1,12 @@
+/* Program variables */
+DEFINE VARIABLE minId AS INTEGER NO-UNDO INITIAL 1.
+DEFINE VARIABLE custName AS CHARACTER NO-UNDO INITIAL "Mary".
+
+/* Variables are bound as parameters inside WHERE clauses */
+FOR EACH Customer WHERE Id > minId:
+    DISPLAY Name Id.
+END.
+
+FIND FIRST Customer WHERE Name = custName.
+custName = Customer.Name.
+DISPLAY custName minId.